@@ -0,0 +1,125 @@
+use crate::error::*;
+
+//-----------------------------------------------------
+
+/// convert amount between mints of different decimals with checked arithmetic, rounded down
+pub fn convert_decimals(amount: u64, from_decimals: u8, to_decimals: u8) -> Result<u64> {
+    let amount = amount as u128;
+
+    let converted = if from_decimals > to_decimals {
+        let divisor = 10u128
+            .checked_pow((from_decimals - to_decimals) as u32)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        amount / divisor
+    } else {
+        10u128
+            .checked_pow((to_decimals - from_decimals) as u32)
+            .and_then(|multiplier| amount.checked_mul(multiplier))
+            .ok_or(OneRingFinanceError::MathOverflow)?
+    };
+
+    Ok(u64::try_from(converted).map_err(|_| OneRingFinanceError::MathOverflow)?)
+}
+
+//-----------------------------------------------------
+
+/// stable token amount, in stable mint decimals
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StableAmount(pub u64);
+
+/// 1USD amount, in 1USD mint decimals
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OusdAmount(pub u64);
+
+impl StableAmount {
+    /// 1USD amount to be minted for stable amount,
+    /// rounded down against the user, dust stays in the vault
+    pub fn to_ousd(self, stable_decimals: u8, ousd_decimals: u8) -> Result<OusdAmount> {
+        convert_decimals(self.0, stable_decimals, ousd_decimals).map(OusdAmount)
+    }
+}
+
+impl OusdAmount {
+    /// stable amount to be paid out for 1USD amount,
+    /// rounded down against the user, dust stays in the vault
+    pub fn to_stable(self, stable_decimals: u8, ousd_decimals: u8) -> Result<StableAmount> {
+        convert_decimals(self.0, ousd_decimals, stable_decimals).map(StableAmount)
+    }
+}
+
+//-----------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_DECIMALS: u8 = 18;
+
+    #[test]
+    fn test_same_decimals() {
+        for decimals in 0..=MAX_DECIMALS {
+            for amount in [0, 1, 999, u64::MAX] {
+                assert_eq!(
+                    StableAmount(amount).to_ousd(decimals, decimals).unwrap(),
+                    OusdAmount(amount)
+                );
+                assert_eq!(
+                    OusdAmount(amount).to_stable(decimals, decimals).unwrap(),
+                    StableAmount(amount)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for stable_decimals in 0..=MAX_DECIMALS {
+            for ousd_decimals in 0..=MAX_DECIMALS {
+                // one whole token
+                let stable = StableAmount(10u64.pow(stable_decimals as u32));
+
+                let ousd = stable.to_ousd(stable_decimals, ousd_decimals).unwrap();
+                assert_eq!(ousd, OusdAmount(10u64.pow(ousd_decimals as u32)));
+                assert_eq!(
+                    ousd.to_stable(stable_decimals, ousd_decimals).unwrap(),
+                    stable
+                );
+
+                // never pays out more than deposited, with dust
+                let stable = StableAmount(10u64.pow(stable_decimals as u32) + 7);
+                let ousd = stable.to_ousd(stable_decimals, ousd_decimals).unwrap();
+                assert!(ousd.to_stable(stable_decimals, ousd_decimals).unwrap() <= stable);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rounding_against_user() {
+        // USDC-like 6 decimals stable, 9 decimals 1USD
+        assert_eq!(OusdAmount(1_999).to_stable(6, 9).unwrap(), StableAmount(1));
+        assert_eq!(OusdAmount(999).to_stable(6, 9).unwrap(), StableAmount(0));
+
+        // 18 decimals stable, 9 decimals 1USD
+        assert_eq!(
+            StableAmount(1_999_999_999).to_ousd(18, 9).unwrap(),
+            OusdAmount(1)
+        );
+        assert_eq!(
+            StableAmount(999_999_999).to_ousd(18, 9).unwrap(),
+            OusdAmount(0)
+        );
+
+        assert_eq!(convert_decimals(1_999, 9, 6).unwrap(), 1);
+        assert_eq!(convert_decimals(0, 9, 6).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert!(StableAmount(u64::MAX).to_ousd(6, 9).is_err());
+        assert!(OusdAmount(u64::MAX / 1_000 + 1).to_stable(12, 9).is_err());
+        assert!(StableAmount(1).to_ousd(0, 18).is_ok());
+        assert!(StableAmount(19).to_ousd(0, 18).is_err());
+        assert_eq!(convert_decimals(u64::MAX, 18, 0).unwrap(), 18);
+    }
+}
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::{constant::*, error::*};
+
 //-----------------------------------------------------
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
@@ -7,29 +9,336 @@ pub struct CreateReserveArgs {
     pub nonce: u8,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct MigrateReserveArgs {
+    pub nonce: u8,
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
 pub struct DepositOrWithdrawArgs {
     pub amount: u64,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct DepositArgs {
+    /// 1USD amount
+    pub amount: u64,
+    /// index into [crate::constant::LOCK_TIERS], [crate::constant::LOCK_TIER_NONE] if not locked
+    pub lock_tier: u8,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct TransferReserveArgs {
+    /// bump seed of recipient reserve PDA
+    pub recipient_nonce: u8,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct SetDelegateArgs {
+    /// address allowed to compound rewards of the reserve
+    pub delegate: Pubkey,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct MintOusdArgs {
+    /// stable token amount
+    pub amount: u64,
+    /// minimum 1USD amount received, after fees
+    pub min_amount_out: u64,
+    /// unix timestamp after which the transaction is rejected
+    pub expiry: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct RedeemArgs {
+    /// 1USD amount
+    pub amount: u64,
+    /// minimum stable token amount received, after fees
+    pub min_amount_out: u64,
+    /// unix timestamp after which the transaction is rejected
+    pub expiry: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct SwapStableArgs {
+    /// input stable token amount
+    pub amount: u64,
+    /// minimum output stable token amount received, after fees
+    pub min_amount_out: u64,
+    /// unix timestamp after which the transaction is rejected
+    pub expiry: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct FlashMintArgs {
+    /// 1USD amount, repaid with fee in the same transaction
+    pub amount: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct FlashRepayArgs {
+    /// transaction instruction index of the repaid flash mint
+    pub flash_mint_index: u16,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct QueueRedemptionArgs {
+    pub nonce: u8,
+    pub amount: u64,
+}
+
+impl QueueRedemptionArgs {
+    /// check queued amount, a ticket of nothing would only hold up the queue
+    pub fn validate(&self) -> ProgramResult {
+        if self.amount == 0 {
+            return Err(OneRingFinanceError::ZeroAmount.into());
+        }
+
+        Ok(())
+    }
+}
+
 //-----------------------------------------------------
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
 pub struct CreateMarketArgs {
+    pub market_bump: u8,
     pub stable_vault_bump: u8,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateMarketArgs {
+    pub lock_flag: bool,
+    pub withdrawal_liq: u64,
+    /// add `withdrawal_liq` to the current liquidity instead of overwriting it
+    pub increment_withdrawal_liq: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct LockMarketArgs {
+    pub lock_flag: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateCapsArgs {
+    /// upper bound of 1USD minted against the market, 0 if uncapped
+    pub supply_cap: u64,
+    /// upper bound of 1USD minted against all markets, 0 if uncapped
+    pub global_cap: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateRateLimitsArgs {
+    /// rate limit window in seconds, 0 disables rate limits
+    pub window: i64,
+    /// maximum 1USD mint volume per window, 0 if unlimited
+    pub mint_limit: u64,
+    /// maximum 1USD redeem volume per window, 0 if unlimited
+    pub redeem_limit: u64,
+}
+
+impl UpdateRateLimitsArgs {
+    /// check rate limit window
+    pub fn validate(&self) -> ProgramResult {
+        if self.window < 0 {
+            return Err(OneRingFinanceError::InvalidRateLimitWindow.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateOracleArgs {
+    /// price feed account, ignored if oracle source is none
+    pub oracle: Pubkey,
+    /// price feed layout, none disables the depeg guard
+    pub oracle_source: u8,
+    /// minimum stable price to mint, in basis points of $1
+    pub min_price_bps: u16,
+    /// maximum price age in seconds
+    pub max_price_age: i64,
+}
+
+impl UpdateOracleArgs {
+    /// check stable price feed and depeg guard parameters
+    pub fn validate(&self) -> ProgramResult {
+        if self.oracle_source != ORACLE_SOURCE_NONE
+            && (self.oracle_source > ORACLE_SOURCE_SWITCHBOARD
+                || self.oracle == Pubkey::default()
+                || self.min_price_bps as u64 > PRICE_BPS_DENOMINATOR
+                || self.max_price_age <= 0)
+        {
+            return Err(OneRingFinanceError::InvalidOracleConfig.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateFeesArgs {
+    pub mint_fee_bps: u16,
+    pub redeem_fee_bps: u16,
+    pub swap_fee_bps: u16,
+}
+
+impl UpdateFeesArgs {
+    /// check mint, redeem & swap fees
+    pub fn validate(&self) -> ProgramResult {
+        if self.mint_fee_bps > MAX_FEE_BPS
+            || self.redeem_fee_bps > MAX_FEE_BPS
+            || self.swap_fee_bps > MAX_FEE_BPS
+        {
+            return Err(OneRingFinanceError::FeeTooHigh.into());
+        }
+
+        Ok(())
+    }
+}
+
 //-----------------------------------------------------
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
 pub struct CreateAdminArgs {
+    pub state_bump: u8,
     pub ousd_mint_auth_bump: u8,
     pub stable_vault_auth_bump: u8,
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
 pub struct UpdateStateArgs {
-    pub emergency_flag: bool,
+    /// a set of `PAUSE_*` bits
+    pub pause_flags: u8,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateFlashMintArgs {
+    /// upper bound of 1USD flash minted at once, 0 disables flash mint
+    pub flash_mint_cap: u64,
+    pub flash_fee_bps: u16,
+}
+
+impl UpdateFlashMintArgs {
+    /// check flash mint fee
+    pub fn validate(&self) -> ProgramResult {
+        if self.flash_fee_bps > MAX_FEE_BPS {
+            return Err(OneRingFinanceError::FeeTooHigh.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateWithdrawCooldownArgs {
+    /// delay between request_withdraw and complete_withdraw in seconds
+    pub withdraw_cooldown: i64,
+    /// fee of instant withdraw, in basis points
+    pub early_exit_fee_bps: u16,
+}
+
+impl UpdateWithdrawCooldownArgs {
+    /// check withdraw cooldown & early exit fee
+    pub fn validate(&self) -> ProgramResult {
+        if !(0..=MAX_WITHDRAW_COOLDOWN).contains(&self.withdraw_cooldown) {
+            return Err(OneRingFinanceError::WithdrawCooldownTooLong.into());
+        }
+        if self.early_exit_fee_bps > MAX_FEE_BPS {
+            return Err(OneRingFinanceError::FeeTooHigh.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateRolesArgs {
+    pub guardian: Pubkey,
+    pub operator: Pubkey,
+    pub harvester: Pubkey,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct DistributeRewardsArgs {
+    /// harvested stable token amount transferred into the vault, may be 0
+    pub harvest_amount: u64,
+    /// 1USD reward amount credited to main state
+    pub amount: u64,
 }
 
 //-----------------------------------------------------
+
+/// admin action applied by [execute_queued_action] once its timelock expires
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub enum TimelockAction {
+    SetTimelockDelay {
+        delay: i64,
+    },
+    ProposeAdmin {
+        new_admin: Pubkey,
+    },
+    UpdateRoles {
+        args: UpdateRolesArgs,
+    },
+    UpdateMarket {
+        market: Pubkey,
+        args: UpdateMarketArgs,
+    },
+    UpdateFees {
+        market: Pubkey,
+        args: UpdateFeesArgs,
+    },
+    UpdateCaps {
+        market: Pubkey,
+        args: UpdateCapsArgs,
+    },
+    UpdateRateLimits {
+        market: Pubkey,
+        args: UpdateRateLimitsArgs,
+    },
+    UpdateOracle {
+        market: Pubkey,
+        args: UpdateOracleArgs,
+    },
+    UpdateFlashMint {
+        args: UpdateFlashMintArgs,
+    },
+    UpdateWithdrawCooldown {
+        args: UpdateWithdrawCooldownArgs,
+    },
+    UpdateTreasury {
+        treasury: Pubkey,
+    },
+}
+
+impl TimelockAction {
+    /// check action arguments, on queueing and again on execution
+    pub fn validate(&self) -> ProgramResult {
+        match self {
+            TimelockAction::SetTimelockDelay { delay } => {
+                if !(0..=MAX_TIMELOCK_DELAY).contains(delay) {
+                    return Err(OneRingFinanceError::TimelockDelayTooLong.into());
+                }
+
+                Ok(())
+            }
+            TimelockAction::UpdateFees { args, .. } => args.validate(),
+            TimelockAction::UpdateRateLimits { args, .. } => args.validate(),
+            TimelockAction::UpdateOracle { args, .. } => args.validate(),
+            TimelockAction::UpdateFlashMint { args } => args.validate(),
+            TimelockAction::UpdateWithdrawCooldown { args } => args.validate(),
+            TimelockAction::ProposeAdmin { .. }
+            | TimelockAction::UpdateRoles { .. }
+            | TimelockAction::UpdateMarket { .. }
+            | TimelockAction::UpdateCaps { .. }
+            | TimelockAction::UpdateTreasury { .. } => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct QueueActionArgs {
+    pub nonce: u8,
+    pub action: TimelockAction,
+}
@@ -1,3 +1,9 @@
+/// main state PDA seed
+pub const STATE_SEED: &[u8] = b"or_state";
+
+/// market PDA seed
+pub const MARKET_SEED: &[u8] = b"or_market";
+
 /// 1USD mint authority seed
 pub const OUSD_MINT_AUTH_SEED: &[u8] = b"or_ousd_mint_auth";
 
@@ -6,3 +12,89 @@ pub const STABLE_VAULT_SEED: &[u8] = b"or_stable_vault";
 
 /// reserve PDA seed
 pub const RESERVE_SEED: &[u8] = b"or_reserve";
+
+/// redemption ticket PDA seed, along with owner, market and queue id
+pub const REDEEM_TICKET_SEED: &[u8] = b"or_redeem_ticket";
+
+/// timelocked action PDA seed
+pub const QUEUED_ACTION_SEED: &[u8] = b"or_queued_action";
+
+/// fixed-point scale of the reward index, reward per deposited 1USD
+pub const REWARD_INDEX_SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// fee denominator, fees are in basis points
+pub const FEE_BPS_DENOMINATOR: u64 = 10_000;
+
+/// upper bound of mint & redeem fees, 1%
+pub const MAX_FEE_BPS: u16 = 100;
+
+/// price denominator, oracle prices are in basis points of $1
+pub const PRICE_BPS_DENOMINATOR: u64 = 10_000;
+
+/// market without price feed
+pub const ORACLE_SOURCE_NONE: u8 = 0;
+
+/// pyth price feed
+pub const ORACLE_SOURCE_PYTH: u8 = 1;
+
+/// switchboard v2 aggregator price feed
+pub const ORACLE_SOURCE_SWITCHBOARD: u8 = 2;
+
+/// lock weight denominator, deposits weighted [LOCK_WEIGHT_BASE] earn rewards at 1x
+pub const LOCK_WEIGHT_BASE: u16 = 10_000;
+
+/// no lock, deposits can be withdrawn anytime
+pub const LOCK_TIER_NONE: u8 = 0;
+
+/// lock tiers chosen at deposit, indexed by tier: (lock duration in seconds, reward weight)
+pub const LOCK_TIERS: [(i64, u16); 4] = [
+    (0, LOCK_WEIGHT_BASE),
+    // 1 month, 1.25x
+    (30 * 24 * 60 * 60, 12_500),
+    // 3 months, 1.5x
+    (90 * 24 * 60 * 60, 15_000),
+    // 12 months, 2x
+    (365 * 24 * 60 * 60, 20_000),
+];
+
+/// upper bound of withdraw cooldown, 30 days
+pub const MAX_WITHDRAW_COOLDOWN: i64 = 30 * 24 * 60 * 60;
+
+/// upper bound of timelock delay, 30 days
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * 24 * 60 * 60;
+
+/// anchor sighash of flash_mint instruction
+pub const FLASH_MINT_DISCRIMINATOR: [u8; 8] = [20, 32, 101, 179, 178, 157, 106, 170];
+
+/// anchor sighash of flash_repay instruction
+pub const FLASH_REPAY_DISCRIMINATOR: [u8; 8] = [182, 143, 19, 23, 39, 221, 184, 78];
+
+/// pause flag of mint_ousd, mint_and_deposit & flash_mint
+pub const PAUSE_MINT: u8 = 1 << 0;
+
+/// pause flag of redeem & redemption queue
+pub const PAUSE_REDEEM: u8 = 1 << 1;
+
+/// pause flag of deposit, mint_and_deposit & claim_and_deposit
+pub const PAUSE_DEPOSIT: u8 = 1 << 2;
+
+/// pause flag of withdraw
+pub const PAUSE_WITHDRAW: u8 = 1 << 3;
+
+/// pause flag of claim & claim_and_deposit
+pub const PAUSE_CLAIM: u8 = 1 << 4;
+
+/// pause flag of yield strategy moves and reward distribution
+pub const PAUSE_STRATEGY: u8 = 1 << 5;
+
+/// pause flag of swap_stable
+pub const PAUSE_SWAP: u8 = 1 << 6;
+
+/// all pause flags
+pub const PAUSE_ALL: u8 = PAUSE_MINT
+    | PAUSE_REDEEM
+    | PAUSE_DEPOSIT
+    | PAUSE_WITHDRAW
+    | PAUSE_CLAIM
+    | PAUSE_STRATEGY
+    | PAUSE_SWAP;
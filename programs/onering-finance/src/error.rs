@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 
+/// new variants are appended only, error codes of deployed variants must not shift
 #[error]
 pub enum OneRingFinanceError {
     #[msg("Access denied")]
@@ -32,4 +33,173 @@ pub enum OneRingFinanceError {
     InsufficientOusdBalance,
     #[msg("Insufficient withdrawal liquidity")]
     InsufficientWithdrawalLiquidity,
+
+    #[msg("No deposit liquidity to distribute rewards")]
+    NoDepositLiquidity,
+
+    #[msg("Math operation overflow")]
+    MathOverflow,
+
+    #[msg("Stable vault is invalid")]
+    InvalidStableVault,
+
+    #[msg("Reward amount exceeds surplus of vault assets")]
+    InsufficientSurplus,
+
+    #[msg("Redemption ticket is not at the queue head")]
+    NotQueueHead,
+    #[msg("Redemption ticket already cancelled")]
+    TicketCancelled,
+
+    #[msg("Treasury token account is invalid")]
+    InvalidTreasury,
+
+    #[msg("Fee exceeds the upper bound")]
+    FeeTooHigh,
+
+    #[msg("Slippage exceeded")]
+    SlippageExceeded,
+    #[msg("Transaction expired")]
+    Expired,
+
+    #[msg("No pending admin")]
+    NoPendingAdmin,
+
+    #[msg("Timelock required, queue the action instead")]
+    TimelockRequired,
+
+    #[msg("Timelocked action is not ready")]
+    TimelockNotReady,
+
+    #[msg("Timelock delay exceeds the upper bound")]
+    TimelockDelayTooLong,
+
+    #[msg("Accounts do not match the queued action")]
+    InvalidQueuedAction,
+
+    #[msg("Pause flags are invalid")]
+    InvalidPauseFlags,
+
+    #[msg("1USD mint authority is not the mint authority PDA")]
+    InvalidOusdMintAuthority,
+
+    #[msg("1USD freeze authority is neither absent nor the mint authority PDA")]
+    InvalidOusdFreezeAuthority,
+
+    #[msg("Mint cap exceeded")]
+    CapExceeded,
+
+    #[msg("Mint volume exceeds the rate limit of the current window")]
+    MintRateLimitExceeded,
+
+    #[msg("Redeem volume exceeds the rate limit of the current window")]
+    RedeemRateLimitExceeded,
+
+    #[msg("Rate limit window is invalid")]
+    InvalidRateLimitWindow,
+
+    #[msg("Oracle account is invalid")]
+    InvalidOracle,
+
+    #[msg("Oracle configuration is invalid")]
+    InvalidOracleConfig,
+
+    #[msg("Oracle price is stale")]
+    StalePrice,
+
+    #[msg("Stable price is below the mint threshold")]
+    StablePriceTooLow,
+
+    #[msg("Cannot swap a stable token for itself")]
+    SameStableSwap,
+
+    #[msg("Flash mint amount exceeds the cap")]
+    FlashMintCapExceeded,
+
+    #[msg("Flash mint is not repaid in the same transaction")]
+    FlashRepayMissing,
+
+    #[msg("Flash repay does not match a flash mint")]
+    InvalidFlashMint,
+
+    #[msg("Share mode is disabled")]
+    ShareModeDisabled,
+
+    #[msg("Share mode is already enabled")]
+    ShareModeEnabled,
+
+    #[msg("Share mint is invalid")]
+    InvalidShareMint,
+
+    #[msg("Share token account owner is invalid")]
+    InvalidShareAccountOwner,
+
+    #[msg("Deposit is locked until the unlock time")]
+    ReserveLocked,
+
+    #[msg("Lock tier is invalid")]
+    InvalidLockTier,
+
+    #[msg("No pending withdrawal")]
+    NoPendingWithdraw,
+
+    #[msg("Withdraw cooldown is not over")]
+    WithdrawCooldownNotOver,
+
+    #[msg("Withdraw cooldown exceeds the upper bound")]
+    WithdrawCooldownTooLong,
+
+    #[msg("Reserve is not empty")]
+    ReserveNotEmpty,
+
+    #[msg("Market is not empty")]
+    MarketNotEmpty,
+
+    #[msg("Cannot transfer a reserve to itself")]
+    SameReserveTransfer,
+
+    #[msg("Redemption ticket is not escrowed")]
+    TicketNotEscrowed,
+
+    #[msg("Amount must be greater than zero")]
+    ZeroAmount,
+
+    #[msg("Withdrawal liquidity is sufficient, redeem instead")]
+    WithdrawalLiquiditySufficient,
+
+    #[msg("Share amount must be greater than zero")]
+    ZeroShares,
+}
+
+//-----------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deployed_error_codes() {
+        let deployed = [
+            OneRingFinanceError::AccessDenied,
+            OneRingFinanceError::ServiceDisabled,
+            OneRingFinanceError::MarketLocked,
+            OneRingFinanceError::ReserveFrozen,
+            OneRingFinanceError::InvalidStableMint,
+            OneRingFinanceError::InvalidOusdMint,
+            OneRingFinanceError::InvalidStableAccountOwner,
+            OneRingFinanceError::InvalidOusdAccountOwner,
+            OneRingFinanceError::WithdrawalAmountTooMuch,
+            OneRingFinanceError::ClaimAmountTooMuch,
+            OneRingFinanceError::InsufficientStableBalance,
+            OneRingFinanceError::InsufficientOusdBalance,
+            OneRingFinanceError::InsufficientWithdrawalLiquidity,
+        ];
+
+        for (i, err) in deployed.into_iter().enumerate() {
+            assert_eq!(
+                ProgramError::from(err),
+                ProgramError::Custom(6000 + i as u32)
+            );
+        }
+    }
 }
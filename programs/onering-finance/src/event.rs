@@ -0,0 +1,649 @@
+use anchor_lang::prelude::*;
+
+use crate::args::*;
+
+//-----------------------------------------------------
+
+/// emitted by [create_admin]
+#[event]
+pub struct CreateAdminEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    pub ousd_mint: Pubkey,
+    pub treasury: Pubkey,
+}
+
+/// emitted by [migrate_state]
+#[event]
+pub struct MigrateStateEvent {
+    pub admin: Pubkey,
+    pub legacy_state: Pubkey,
+    pub state: Pubkey,
+    pub ousd_mint: Pubkey,
+}
+
+/// emitted by [migrate_market]
+#[event]
+pub struct MigrateMarketEvent {
+    pub admin: Pubkey,
+    pub legacy_market: Pubkey,
+    pub market: Pubkey,
+    pub stable_mint: Pubkey,
+    pub amount: u64,
+}
+
+/// emitted by [migrate_reserve]
+#[event]
+pub struct MigrateReserveEvent {
+    pub initializer: Pubkey,
+    pub legacy_reserve: Pubkey,
+    pub reserve: Pubkey,
+    pub deposit_amount: u64,
+    pub reward_amount: u64,
+}
+
+/// emitted by [propose_admin]
+#[event]
+pub struct ProposeAdminEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+/// emitted by [accept_admin]
+#[event]
+pub struct AcceptAdminEvent {
+    pub old_admin: Pubkey,
+    pub admin: Pubkey,
+    pub state: Pubkey,
+}
+
+/// emitted by [cancel_admin_transfer]
+#[event]
+pub struct CancelAdminTransferEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+/// emitted by [update_state]
+#[event]
+pub struct UpdateStateEvent {
+    pub authority: Pubkey,
+    pub state: Pubkey,
+    pub pause_flags: u8,
+}
+
+/// emitted by [update_roles]
+#[event]
+pub struct UpdateRolesEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    pub guardian: Pubkey,
+    pub operator: Pubkey,
+    pub harvester: Pubkey,
+}
+
+/// emitted by [update_treasury]
+#[event]
+pub struct UpdateTreasuryEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    pub treasury: Pubkey,
+}
+
+/// emitted by [update_flash_mint]
+#[event]
+pub struct UpdateFlashMintEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    pub flash_mint_cap: u64,
+    pub flash_fee_bps: u16,
+}
+
+/// emitted by [update_withdraw_cooldown]
+#[event]
+pub struct UpdateWithdrawCooldownEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    pub withdraw_cooldown: i64,
+    pub early_exit_fee_bps: u16,
+}
+
+/// emitted by [enable_share_mode]
+#[event]
+pub struct EnableShareModeEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    pub share_mint: Pubkey,
+}
+
+/// emitted by [distribute_rewards]
+#[event]
+pub struct DistributeRewardsEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    /// harvested stable token amount transferred into the vault
+    pub harvest_amount: u64,
+    /// 1USD reward amount
+    pub amount: u64,
+    pub reward_index: u128,
+    pub total_deposit_amount: u64,
+    pub total_reward_amount: u64,
+}
+
+//-----------------------------------------------------
+
+/// emitted by [create_market]
+#[event]
+pub struct CreateMarketEvent {
+    pub admin: Pubkey,
+    pub market: Pubkey,
+    pub stable_mint: Pubkey,
+    pub stable_vault: Pubkey,
+}
+
+/// emitted by [close_market]
+#[event]
+pub struct CloseMarketEvent {
+    pub admin: Pubkey,
+    pub market: Pubkey,
+    pub stable_mint: Pubkey,
+    /// leftover stable token amount swept to treasury
+    pub swept_amount: u64,
+}
+
+/// emitted by [update_market]
+#[event]
+pub struct UpdateMarketEvent {
+    pub admin: Pubkey,
+    pub market: Pubkey,
+    pub lock_flag: bool,
+    pub withdrawal_liq: u64,
+}
+
+/// emitted by [lock_market]
+#[event]
+pub struct LockMarketEvent {
+    pub authority: Pubkey,
+    pub market: Pubkey,
+    pub lock_flag: bool,
+}
+
+/// emitted by [update_caps]
+#[event]
+pub struct UpdateCapsEvent {
+    pub admin: Pubkey,
+    pub market: Pubkey,
+    pub supply_cap: u64,
+    pub global_cap: u64,
+}
+
+/// emitted by [update_rate_limits]
+#[event]
+pub struct UpdateRateLimitsEvent {
+    pub admin: Pubkey,
+    pub market: Pubkey,
+    pub window: i64,
+    pub mint_limit: u64,
+    pub redeem_limit: u64,
+}
+
+/// emitted by [update_oracle]
+#[event]
+pub struct UpdateOracleEvent {
+    pub admin: Pubkey,
+    pub market: Pubkey,
+    pub oracle: Pubkey,
+    pub oracle_source: u8,
+    pub min_price_bps: u16,
+    pub max_price_age: i64,
+}
+
+/// emitted by [update_fees]
+#[event]
+pub struct UpdateFeesEvent {
+    pub admin: Pubkey,
+    pub market: Pubkey,
+    pub mint_fee_bps: u16,
+    pub redeem_fee_bps: u16,
+    pub swap_fee_bps: u16,
+}
+
+//-----------------------------------------------------
+
+/// emitted by [queue_action]
+#[event]
+pub struct QueueActionEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    pub action_id: u64,
+    pub action: TimelockAction,
+    pub eta: i64,
+}
+
+/// emitted by [execute_queued_action]
+#[event]
+pub struct ExecuteQueuedActionEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    pub action_id: u64,
+    pub action: TimelockAction,
+}
+
+/// emitted by [cancel_queued_action]
+#[event]
+pub struct CancelQueuedActionEvent {
+    pub admin: Pubkey,
+    pub state: Pubkey,
+    pub action_id: u64,
+}
+
+//-----------------------------------------------------
+
+/// emitted by [mint_ousd]
+#[event]
+pub struct MintOusdEvent {
+    pub initializer: Pubkey,
+    pub market: Pubkey,
+    /// stable token amount deposited
+    pub stable_amount: u64,
+    /// 1USD amount equivalant to stable token amount, fee included
+    pub ousd_amount: u64,
+    /// 1USD amount sent to treasury
+    pub fee_amount: u64,
+    pub ousd_supply: u64,
+}
+
+/// emitted by [redeem]
+#[event]
+pub struct RedeemEvent {
+    pub initializer: Pubkey,
+    pub market: Pubkey,
+    /// 1USD amount redeemed, fee included
+    pub ousd_amount: u64,
+    /// 1USD amount sent to treasury
+    pub fee_amount: u64,
+    /// stable token amount paid out
+    pub stable_amount: u64,
+    pub withdrawal_liq: u64,
+    pub ousd_supply: u64,
+}
+
+/// emitted by [queue_redemption]
+#[event]
+pub struct QueueRedemptionEvent {
+    pub initializer: Pubkey,
+    pub market: Pubkey,
+    pub ticket: Pubkey,
+    pub queue_id: u64,
+    /// number of tickets ahead in the redemption queue
+    pub queue_position: u64,
+    pub ousd_amount: u64,
+    /// redeem fee charged when fulfilled, in basis points
+    pub fee_bps: u16,
+    pub queued_amount: u64,
+}
+
+/// emitted by [fulfill_redemption]
+#[event]
+pub struct FulfillRedemptionEvent {
+    pub initializer: Pubkey,
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub queue_id: u64,
+    /// escrowed 1USD amount, fee included, 0 if cancelled
+    pub ousd_amount: u64,
+    /// 1USD amount sent to treasury
+    pub fee_amount: u64,
+    /// stable token amount paid out
+    pub stable_amount: u64,
+    pub withdrawal_liq: u64,
+    pub queued_amount: u64,
+}
+
+/// emitted by [cancel_redemption]
+#[event]
+pub struct CancelRedemptionEvent {
+    pub initializer: Pubkey,
+    pub market: Pubkey,
+    pub queue_id: u64,
+    pub ousd_amount: u64,
+    pub queued_amount: u64,
+}
+
+/// emitted by [escrow_redemption]
+#[event]
+pub struct EscrowRedemptionEvent {
+    pub initializer: Pubkey,
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub queue_id: u64,
+    /// escrowed 1USD amount, fee included, 0 if cancelled
+    pub ousd_amount: u64,
+    /// 1USD amount sent to treasury
+    pub fee_amount: u64,
+    /// 1USD amount net of fee, claimable by the owner
+    pub escrowed_amount: u64,
+    pub withdrawal_liq: u64,
+    pub queued_amount: u64,
+}
+
+/// emitted by [claim_redemption]
+#[event]
+pub struct ClaimRedemptionEvent {
+    pub initializer: Pubkey,
+    pub market: Pubkey,
+    pub queue_id: u64,
+    /// escrowed 1USD amount claimed
+    pub ousd_amount: u64,
+    /// stable token amount paid out
+    pub stable_amount: u64,
+}
+
+/// emitted by [get_queue_position]
+#[event]
+pub struct QueuePositionEvent {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub ticket: Pubkey,
+    pub queue_id: u64,
+    /// number of tickets ahead in the redemption queue
+    pub queue_position: u64,
+    pub ousd_amount: u64,
+    /// 1USD amount waiting in the queue of the market
+    pub queued_amount: u64,
+}
+
+/// emitted by [flash_mint]
+#[event]
+pub struct FlashMintEvent {
+    pub initializer: Pubkey,
+    pub state: Pubkey,
+    /// 1USD amount flash minted
+    pub amount: u64,
+    /// transaction instruction index of the flash repay
+    pub flash_repay_index: u16,
+}
+
+/// emitted by [flash_repay]
+#[event]
+pub struct FlashRepayEvent {
+    pub initializer: Pubkey,
+    pub state: Pubkey,
+    /// 1USD amount burnt
+    pub amount: u64,
+    /// 1USD amount sent to treasury
+    pub fee_amount: u64,
+}
+
+/// emitted by [swap_stable]
+#[event]
+pub struct SwapStableEvent {
+    pub initializer: Pubkey,
+    pub market_in: Pubkey,
+    pub market_out: Pubkey,
+    /// input stable token amount
+    pub stable_amount_in: u64,
+    /// 1USD value of input, fee included
+    pub ousd_amount: u64,
+    /// 1USD value kept in the input vault as fee
+    pub fee_amount: u64,
+    /// output stable token amount paid out
+    pub stable_amount_out: u64,
+    /// withdrawal liquidity left in the output market
+    pub withdrawal_liq: u64,
+}
+
+//-----------------------------------------------------
+
+/// emitted by [create_reserve]
+#[event]
+pub struct CreateReserveEvent {
+    pub initializer: Pubkey,
+    pub reserve: Pubkey,
+}
+
+/// emitted by [transfer_reserve]
+#[event]
+pub struct TransferReserveEvent {
+    pub initializer: Pubkey,
+    pub recipient: Pubkey,
+    pub reserve: Pubkey,
+    pub recipient_reserve: Pubkey,
+    /// 1USD amount
+    pub deposit_amount: u64,
+    /// 1USD amount
+    pub reward_amount: u64,
+    pub unlock_time: i64,
+    pub lock_weight: u16,
+}
+
+/// emitted by [set_delegate]
+#[event]
+pub struct SetDelegateEvent {
+    pub initializer: Pubkey,
+    pub reserve: Pubkey,
+    pub delegate: Pubkey,
+}
+
+/// emitted by [revoke_delegate]
+#[event]
+pub struct RevokeDelegateEvent {
+    pub initializer: Pubkey,
+    pub reserve: Pubkey,
+}
+
+/// emitted by [close_reserve]
+#[event]
+pub struct CloseReserveEvent {
+    pub initializer: Pubkey,
+    pub reserve: Pubkey,
+}
+
+/// emitted by [deposit]
+#[event]
+pub struct DepositEvent {
+    pub initializer: Pubkey,
+    pub reserve: Pubkey,
+    /// 1USD amount
+    pub amount: u64,
+    pub deposit_amount: u64,
+    pub reward_amount: u64,
+    pub total_deposit_amount: u64,
+    pub total_reward_amount: u64,
+    pub unlock_time: i64,
+    pub lock_weight: u16,
+}
+
+/// emitted by [withdraw]
+#[event]
+pub struct WithdrawEvent {
+    pub initializer: Pubkey,
+    pub reserve: Pubkey,
+    /// 1USD amount
+    pub amount: u64,
+    /// early exit fee, 1USD amount
+    pub fee_amount: u64,
+    pub deposit_amount: u64,
+    pub reward_amount: u64,
+    pub total_deposit_amount: u64,
+    pub total_reward_amount: u64,
+}
+
+/// emitted by [request_withdraw]
+#[event]
+pub struct RequestWithdrawEvent {
+    pub initializer: Pubkey,
+    pub reserve: Pubkey,
+    /// 1USD amount
+    pub amount: u64,
+    pub pending_amount: u64,
+    pub withdrawable_time: i64,
+    pub deposit_amount: u64,
+    pub total_deposit_amount: u64,
+    pub total_pending_withdraw_amount: u64,
+}
+
+/// emitted by [complete_withdraw]
+#[event]
+pub struct CompleteWithdrawEvent {
+    pub initializer: Pubkey,
+    pub reserve: Pubkey,
+    /// 1USD amount
+    pub amount: u64,
+    pub total_pending_withdraw_amount: u64,
+}
+
+/// emitted by [claim]
+#[event]
+pub struct ClaimEvent {
+    pub initializer: Pubkey,
+    pub reserve: Pubkey,
+    /// 1USD amount
+    pub amount: u64,
+    pub deposit_amount: u64,
+    pub reward_amount: u64,
+    pub total_deposit_amount: u64,
+    pub total_reward_amount: u64,
+}
+
+/// emitted by [claim_and_deposit]
+#[event]
+pub struct ClaimAndDepositEvent {
+    /// reserve owner or its delegate
+    pub initializer: Pubkey,
+    pub owner: Pubkey,
+    pub reserve: Pubkey,
+    /// 1USD amount
+    pub amount: u64,
+    pub deposit_amount: u64,
+    pub reward_amount: u64,
+    pub total_deposit_amount: u64,
+    pub total_reward_amount: u64,
+}
+
+/// emitted by [mint_and_deposit]
+#[event]
+pub struct MintAndDepositEvent {
+    pub initializer: Pubkey,
+    pub market: Pubkey,
+    pub reserve: Pubkey,
+    /// stable token amount deposited
+    pub stable_amount: u64,
+    /// 1USD amount equivalant to stable token amount, fee included
+    pub ousd_amount: u64,
+    /// 1USD amount sent to treasury
+    pub fee_amount: u64,
+    pub deposit_amount: u64,
+    pub reward_amount: u64,
+    pub total_deposit_amount: u64,
+    pub total_reward_amount: u64,
+}
+
+/// emitted by [deposit_shares]
+#[event]
+pub struct DepositSharesEvent {
+    pub initializer: Pubkey,
+    /// 1USD amount
+    pub amount: u64,
+    /// share token amount minted
+    pub shares: u64,
+    pub total_shares: u64,
+    pub total_underlying: u64,
+}
+
+/// emitted by [withdraw_shares]
+#[event]
+pub struct WithdrawSharesEvent {
+    pub initializer: Pubkey,
+    /// 1USD amount
+    pub amount: u64,
+    /// share token amount burnt
+    pub shares: u64,
+    pub total_shares: u64,
+    pub total_underlying: u64,
+}
+
+//-----------------------------------------------------
+
+/// emitted by [saber_deposit]
+#[event]
+pub struct SaberDepositEvent {
+    pub authority: Pubkey,
+    pub swap: Pubkey,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    pub min_mint_amount: u64,
+}
+
+/// emitted by [saber_withdraw]
+#[event]
+pub struct SaberWithdrawEvent {
+    pub authority: Pubkey,
+    pub swap: Pubkey,
+    pub pool_token_amount: u64,
+    pub minimum_token_a_amount: u64,
+    pub minimum_token_b_amount: u64,
+}
+
+/// emitted by [saber_withdraw_one]
+#[event]
+pub struct SaberWithdrawOneEvent {
+    pub authority: Pubkey,
+    pub swap: Pubkey,
+    pub pool_token_amount: u64,
+    pub minimum_token_amount: u64,
+}
+
+/// emitted by [quarry_stake_tokens]
+#[event]
+pub struct QuarryStakeTokensEvent {
+    pub authority: Pubkey,
+    pub quarry: Pubkey,
+    pub miner: Pubkey,
+    pub amount: u64,
+}
+
+/// emitted by [quarry_withdraw_tokens]
+#[event]
+pub struct QuarryWithdrawTokensEvent {
+    pub authority: Pubkey,
+    pub quarry: Pubkey,
+    pub miner: Pubkey,
+    pub amount: u64,
+}
+
+/// emitted by [port_deposit]
+#[event]
+pub struct PortDepositEvent {
+    pub authority: Pubkey,
+    pub reserve: Pubkey,
+    pub amount: u64,
+}
+
+/// emitted by [PortDepositAndCollateralize]
+#[event]
+pub struct PortDepositAndCollateralizeEvent {
+    pub authority: Pubkey,
+    pub reserve: Pubkey,
+    pub amount: u64,
+}
+
+/// emitted by [port_withdraw]
+#[event]
+pub struct PortWithdrawEvent {
+    pub authority: Pubkey,
+    pub reserve: Pubkey,
+    pub amount: u64,
+}
+
+/// emitted by [port_claim_reward]
+#[event]
+pub struct PortClaimRewardEvent {
+    pub authority: Pubkey,
+    pub staking_pool: Pubkey,
+    pub reward_dest: Pubkey,
+}
+
+//-----------------------------------------------------
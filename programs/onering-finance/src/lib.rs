@@ -1,10 +1,12 @@
 use anchor_lang::prelude::*;
 
+pub mod amount;
 pub mod args;
 pub mod constant;
 pub mod error;
 pub mod event;
 pub mod located;
+pub mod oracle;
 pub mod processor;
 pub mod states;
 pub mod traits;
@@ -19,41 +21,179 @@ pub mod onering_finance {
 
     /// initialize a main state, transaction executor is set to as an admin
     pub fn create_admin(ctx: Context<CreateAdmin>, args: CreateAdminArgs) -> ProgramResult {
-        // TODO: validate main state address
         ctx.accounts.process(args)
     }
 
-    /// apply new admin authority of main state
-    pub fn apply_new_admin(ctx: Context<ApplyNewAdmin>) -> ProgramResult {
-        // TODO: validate main state address
+    /// propose new admin authority of main state, takes effect once accepted
+    pub fn propose_admin(ctx: Context<ProposeAdmin>) -> ProgramResult {
         ctx.accounts.process()
     }
 
-    /// update main state
+    /// accept admin authority of main state, signed by the proposed admin
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// cancel a pending admin transfer
+    pub fn cancel_admin_transfer(ctx: Context<CancelAdminTransfer>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// update pause flags of main state, guardian can only set more of them
     pub fn update_state(ctx: Context<UpdateState>, args: UpdateStateArgs) -> ProgramResult {
-        // TODO: validate main state address
+        ctx.accounts.process(args)
+    }
+
+    /// update guardian, operator and harvester roles of main state
+    pub fn update_roles(ctx: Context<UpdateRoles>, args: UpdateRolesArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// update treasury 1USD token receiving mint, redeem, swap & flash mint fees
+    pub fn update_treasury(ctx: Context<UpdateTreasury>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// update flash mint cap & fee
+    pub fn update_flash_mint(ctx: Context<UpdateFlashMint>, args: UpdateFlashMintArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// update withdraw cooldown & early exit fee of instant withdraw
+    pub fn update_withdraw_cooldown(ctx: Context<UpdateWithdrawCooldown>, args: UpdateWithdrawCooldownArgs) -> ProgramResult {
         ctx.accounts.process(args)
     }
 
     /// create a market for stable tokens,
     /// a stable token will have correspond pool address for the market
     pub fn create_market(ctx: Context<CreateMarket>, args: CreateMarketArgs) -> ProgramResult {
-        // TODO: validate market state address
         ctx.accounts.process(args)
     }
 
-    /// mint 1USD token in any stable tokens available
-    pub fn mint_ousd(ctx: Context<MintOusd>, args: DepositOrWithdrawArgs) -> ProgramResult {
-        // TODO: validate market state address
+    /// close a market backing no 1USD, leftover vault balance is swept to treasury and rent is returned to admin
+    pub fn close_market(ctx: Context<CloseMarket>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// update a market, lock flag and withdrawal liquidity
+    pub fn update_market(ctx: Context<UpdateMarket>, args: UpdateMarketArgs) -> ProgramResult {
         ctx.accounts.process(args)
     }
 
+    /// queue a timelocked admin action
+    pub fn queue_action(ctx: Context<QueueAction>, args: QueueActionArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// execute a queued admin action once its timelock expires
+    pub fn execute_queued_action(ctx: Context<ExecuteQueuedAction>) -> ProgramResult {
+        ctx.accounts.process(ctx.remaining_accounts)
+    }
+
+    /// cancel a queued admin action
+    pub fn cancel_queued_action(ctx: Context<CancelQueuedAction>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// lock or unlock a market, guardian can only lock it
+    pub fn lock_market(ctx: Context<LockMarket>, args: LockMarketArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// update mint cap of a market and global mint cap
+    pub fn update_caps(ctx: Context<UpdateCaps>, args: UpdateCapsArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// update mint & redeem rate limits of a market
+    pub fn update_rate_limits(ctx: Context<UpdateRateLimits>, args: UpdateRateLimitsArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// update stable price feed of a market, mint is rejected on depeg or stale price
+    pub fn update_oracle(ctx: Context<UpdateOracle>, args: UpdateOracleArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// update mint, redeem & swap fees of a market, fees are sent to treasury
+    pub fn update_fees(ctx: Context<UpdateFees>, args: UpdateFeesArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// mint 1USD token in any stable tokens available
+    /// stable price feed of the market is passed as a remaining account if configured
+    pub fn mint_ousd(ctx: Context<MintOusd>, args: MintOusdArgs) -> ProgramResult {
+        ctx.accounts.process(args, ctx.remaining_accounts)
+    }
+
     /// redeem 1USD token in USDC
     /// we initially support withdraw in USDC only, so `withdrawal_liquidity` for other markets will be 0.
     /// if we don't have enough `withdrawal_liquidity` in USDC market,
     /// we will let them wait for another one week until we add `withdrawal_liquidity` with the harvested assets from APY farms.
-    pub fn redeem(ctx: Context<Redeem>, args: DepositOrWithdrawArgs) -> ProgramResult {
-        // TODO: validate market state address
+    pub fn redeem(ctx: Context<Redeem>, args: RedeemArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// queue a redemption when `withdrawal_liquidity` is not enough,
+    /// 1USD is escrowed in a redemption ticket until the queue is fulfilled
+    pub fn queue_redemption(ctx: Context<QueueRedemption>, args: QueueRedemptionArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// fulfill a redemption ticket at the queue head, in FIFO order
+    pub fn fulfill_redemption(ctx: Context<FulfillRedemption>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// cancel a redemption ticket, escrowed 1USD is refunded
+    pub fn cancel_redemption(ctx: Context<CancelRedemption>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// escrow a redemption ticket at the queue head whose owner cannot receive stable tokens,
+    /// so that the queue keeps moving; the owner claims it later
+    pub fn escrow_redemption(ctx: Context<EscrowRedemption>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// claim an escrowed redemption ticket in stable tokens
+    pub fn claim_redemption(ctx: Context<ClaimRedemption>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// emit the queue position of a redemption ticket, meant to be simulated
+    pub fn get_queue_position(ctx: Context<GetQueuePosition>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// swap stable tokens between market vaults without minting 1USD
+    /// stable price feeds of both markets are passed as remaining accounts if configured
+    pub fn swap_stable(ctx: Context<SwapStable>, args: SwapStableArgs) -> ProgramResult {
+        ctx.accounts.process(args, ctx.remaining_accounts)
+    }
+
+    /// flash mint 1USD, a flash repay with fee must follow in the same transaction
+    pub fn flash_mint(ctx: Context<FlashMint>, args: FlashMintArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// repay a flash mint of the same transaction, 1USD is burnt and fee is sent to treasury
+    pub fn flash_repay(ctx: Context<FlashRepay>, args: FlashRepayArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// enable share token (s1USD) mode, share mint authority must be 1USD mint authority
+    pub fn enable_share_mode(ctx: Context<EnableShareMode>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// deposit 1USD for auto-compounding share tokens (s1USD)
+    pub fn deposit_shares(ctx: Context<DepositShares>, args: DepositOrWithdrawArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// burn share tokens (s1USD) for 1USD at the current exchange rate
+    pub fn withdraw_shares(ctx: Context<WithdrawShares>, args: DepositOrWithdrawArgs) -> ProgramResult {
         ctx.accounts.process(args)
     }
 
@@ -63,22 +203,36 @@ pub mod onering_finance {
         ctx.accounts.process(args)
     }
 
-    /// deposit (old stake) 1USD token
-    pub fn deposit(ctx: Context<Deposit>, args: DepositOrWithdrawArgs) -> ProgramResult {
+    /// close an empty deposit reserve account, rent is returned to the owner
+    pub fn close_reserve(ctx: Context<CloseReserve>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// deposit (old stake) 1USD token, optionally locked for a boosted reward weight
+    pub fn deposit(ctx: Context<Deposit>, args: DepositArgs) -> ProgramResult {
         ctx.accounts.process(args)
     }
 
     /// mint & deposit
-    pub fn mint_and_deposit(ctx: Context<MintAndDeposit>, args: DepositOrWithdrawArgs) -> ProgramResult {
-        // TODO: validate market state address
-        ctx.accounts.process(args)
+    pub fn mint_and_deposit(ctx: Context<MintAndDeposit>, args: MintOusdArgs) -> ProgramResult {
+        ctx.accounts.process(args, ctx.remaining_accounts)
     }
 
-    /// withdraw (old unstake) 1USD token
+    /// withdraw (old unstake) 1USD token instantly, early exit fee is charged
     pub fn withdraw(ctx: Context<Withdraw>, args: DepositOrWithdrawArgs) -> ProgramResult {
         ctx.accounts.process(args)
     }
 
+    /// request withdraw (old unstake) of 1USD token, completed after the cooldown
+    pub fn request_withdraw(ctx: Context<RequestWithdraw>, args: DepositOrWithdrawArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// complete a requested withdraw once the cooldown is over
+    pub fn complete_withdraw(ctx: Context<CompleteWithdraw>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
     /// claim for accumulated 1USD in reward of deposited (old staked) 1USD tokens
     /// users can withdraw (old unstake) 1USD tokens
     pub fn claim(ctx: Context<Claim>, args: DepositOrWithdrawArgs) -> ProgramResult {
@@ -86,8 +240,44 @@ pub mod onering_finance {
     }
 
     /// claim & deposit
-    /// users claim and deposit (old stake) 1USD tokens
-    pub fn claim_and_deposit(ctx: Context<Claim>, args: DepositOrWithdrawArgs) -> ProgramResult {
+    /// users claim and deposit (old stake) 1USD tokens, the reserve delegate can do it on their behalf
+    pub fn claim_and_deposit(ctx: Context<ClaimAndDeposit>, args: DepositOrWithdrawArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// transfer deposit & unclaimed rewards to the reserve of another wallet, lock included
+    pub fn transfer_reserve(ctx: Context<TransferReserve>, args: TransferReserveArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// set a delegate allowed to compound rewards of a deposit reserve
+    pub fn set_delegate(ctx: Context<SetDelegate>, args: SetDelegateArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// revoke the delegate of a deposit reserve
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> ProgramResult {
+        ctx.accounts.process()
+    }
+
+    /// distribute harvested yield to depositors,
+    /// remaining accounts are (market, stable vault, stable mint) triples of every market
+    pub fn distribute_rewards(ctx: Context<DistributeRewards>, args: DistributeRewardsArgs) -> ProgramResult {
+        ctx.accounts.process(args, ctx.remaining_accounts)
+    }
+
+    /// migrate legacy keypair main state to its PDA, with 1USD mint authority
+    pub fn migrate_state(ctx: Context<MigrateState>, args: CreateAdminArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// migrate a legacy keypair market to its PDA, with stable vault balance
+    pub fn migrate_market(ctx: Context<MigrateMarket>, args: CreateMarketArgs) -> ProgramResult {
+        ctx.accounts.process(args)
+    }
+
+    /// migrate a deposit reserve account derived from legacy main state
+    pub fn migrate_reserve(ctx: Context<MigrateReserve>, args: MigrateReserveArgs) -> ProgramResult {
         ctx.accounts.process(args)
     }
 
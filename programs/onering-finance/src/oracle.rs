@@ -0,0 +1,310 @@
+use crate::{constant::*, error::*};
+
+//-----------------------------------------------------
+
+/// pyth price account magic number
+pub const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+
+/// pyth price account layout version
+pub const PYTH_VERSION: u32 = 2;
+
+/// pyth price account type
+pub const PYTH_ACCOUNT_TYPE_PRICE: u32 = 3;
+
+/// pyth aggregate price status, trading
+pub const PYTH_STATUS_TRADING: u32 = 1;
+
+/// switchboard v2 aggregator account discriminator
+pub const SWITCHBOARD_AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+
+// pyth v2 price account offsets, `PriceAccount` of pyth-sdk-solana 0.8.0 (`repr(C)`):
+// magic, ver, atype, size, ptype, expo, num, num_qt (u32 each), last_slot, valid_slot (u64),
+// ema_price, ema_conf (3 x i64 each), timestamp (i64), min_pub, drv2 (u8), drv3 (u16),
+// drv4 (u32), prod, next (Pubkey), prev_slot, prev_price, prev_conf, prev_timestamp (8 bytes each),
+// agg (price i64, conf u64, status u32, corp_act u32, pub_slot u64), comp ([PriceComp; 32])
+const PYTH_MAGIC_OFFSET: usize = 0;
+const PYTH_VERSION_OFFSET: usize = 4;
+const PYTH_ACCOUNT_TYPE_OFFSET: usize = 8;
+const PYTH_EXPO_OFFSET: usize = 20;
+const PYTH_TIMESTAMP_OFFSET: usize = 96;
+const PYTH_AGG_PRICE_OFFSET: usize = 208;
+const PYTH_AGG_STATUS_OFFSET: usize = 224;
+const PYTH_ACCOUNT_LEN: usize = 240;
+
+// switchboard v2 aggregator account offsets, `AggregatorAccountData` of switchboard-v2
+// (`repr(packed)`) after the 8 bytes anchor discriminator: name (32), metadata (128),
+// _reserved1 (32), queue_pubkey (32), oracle_request_batch_size, min_oracle_results,
+// min_job_results, min_update_delay_seconds (u32 each), start_after (i64),
+// variance_threshold (i128 mantissa, u32 scale), force_report_period, expiration,
+// consecutive_failure_count, next_allowed_update_time (8 bytes each), is_locked (bool),
+// crank_pubkey (32), then latest_confirmed_round: num_success, num_error (u32),
+// is_closed (bool), round_open_slot (u64), round_open_timestamp (i64),
+// result (i128 mantissa, u32 scale)
+const SWITCHBOARD_NUM_SUCCESS_OFFSET: usize = 341;
+const SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET: usize = 358;
+const SWITCHBOARD_MANTISSA_OFFSET: usize = 366;
+const SWITCHBOARD_SCALE_OFFSET: usize = 382;
+const SWITCHBOARD_ACCOUNT_LEN: usize = 386;
+
+//-----------------------------------------------------
+
+/// stable price read from an oracle account
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OraclePrice {
+    /// price in basis points of $1, rounded down
+    pub price_bps: u64,
+
+    /// unix timestamp of the price
+    pub timestamp: i64,
+}
+
+/// parse oracle account data of the given source
+pub fn load_price(oracle_source: u8, data: &[u8]) -> Result<OraclePrice> {
+    match oracle_source {
+        ORACLE_SOURCE_PYTH => load_pyth_price(data),
+        ORACLE_SOURCE_SWITCHBOARD => load_switchboard_price(data),
+        _ => Err(OneRingFinanceError::InvalidOracle.into()),
+    }
+}
+
+/// parse aggregate price of a pyth v2 price account
+pub fn load_pyth_price(data: &[u8]) -> Result<OraclePrice> {
+    if data.len() < PYTH_ACCOUNT_LEN
+        || read_u32(data, PYTH_MAGIC_OFFSET) != PYTH_MAGIC
+        || read_u32(data, PYTH_VERSION_OFFSET) != PYTH_VERSION
+        || read_u32(data, PYTH_ACCOUNT_TYPE_OFFSET) != PYTH_ACCOUNT_TYPE_PRICE
+    {
+        return Err(OneRingFinanceError::InvalidOracle.into());
+    }
+
+    // price is unreliable unless trading
+    if read_u32(data, PYTH_AGG_STATUS_OFFSET) != PYTH_STATUS_TRADING {
+        return Err(OneRingFinanceError::StalePrice.into());
+    }
+
+    let expo = read_u32(data, PYTH_EXPO_OFFSET) as i32;
+    let price = i64::from_le_bytes(read_bytes(data, PYTH_AGG_PRICE_OFFSET));
+
+    Ok(OraclePrice {
+        price_bps: price_bps(price as i128, expo)?,
+        timestamp: i64::from_le_bytes(read_bytes(data, PYTH_TIMESTAMP_OFFSET)),
+    })
+}
+
+/// parse latest confirmed round of a switchboard v2 aggregator account
+pub fn load_switchboard_price(data: &[u8]) -> Result<OraclePrice> {
+    if data.len() < SWITCHBOARD_ACCOUNT_LEN || data[..8] != SWITCHBOARD_AGGREGATOR_DISCRIMINATOR {
+        return Err(OneRingFinanceError::InvalidOracle.into());
+    }
+
+    // no round confirmed yet
+    if read_u32(data, SWITCHBOARD_NUM_SUCCESS_OFFSET) == 0 {
+        return Err(OneRingFinanceError::StalePrice.into());
+    }
+
+    let scale = read_u32(data, SWITCHBOARD_SCALE_OFFSET);
+    let mantissa = i128::from_le_bytes(read_bytes(data, SWITCHBOARD_MANTISSA_OFFSET));
+    let expo = -i32::try_from(scale).map_err(|_| OneRingFinanceError::InvalidOracle)?;
+
+    Ok(OraclePrice {
+        price_bps: price_bps(mantissa, expo)?,
+        timestamp: i64::from_le_bytes(read_bytes(data, SWITCHBOARD_ROUND_OPEN_TIMESTAMP_OFFSET)),
+    })
+}
+
+/// `mantissa * 10^expo` dollars in basis points, rounded down, negative prices are 0
+fn price_bps(mantissa: i128, expo: i32) -> Result<u64> {
+    if mantissa <= 0 {
+        return Ok(0);
+    }
+
+    let bps = (mantissa as u128)
+        .checked_mul(PRICE_BPS_DENOMINATOR as u128)
+        .ok_or(OneRingFinanceError::MathOverflow)?;
+    let scale = 10u128
+        .checked_pow(expo.unsigned_abs())
+        .ok_or(OneRingFinanceError::MathOverflow)?;
+
+    let bps = if expo < 0 {
+        bps / scale
+    } else {
+        bps.checked_mul(scale)
+            .ok_or(OneRingFinanceError::MathOverflow)?
+    };
+
+    Ok(u64::try_from(bps).map_err(|_| OneRingFinanceError::MathOverflow)?)
+}
+
+/// read fixed size bytes at offset, length is checked by the callers
+fn read_bytes<const N: usize>(data: &[u8], offset: usize) -> [u8; N] {
+    data[offset..offset + N].try_into().unwrap()
+}
+
+/// read little endian u32 at offset
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(read_bytes(data, offset))
+}
+
+//-----------------------------------------------------
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// account data written field by field in the upstream declaration order,
+    /// independent of the offset constants under test
+    struct Layout(Vec<u8>);
+
+    impl Layout {
+        fn put(mut self, bytes: &[u8]) -> Self {
+            self.0.extend_from_slice(bytes);
+            self
+        }
+
+        fn skip(self, len: usize) -> Self {
+            self.put(&vec![0u8; len])
+        }
+    }
+
+    pub(crate) fn pyth_account(price: i64, expo: i32, status: u32, timestamp: i64) -> Vec<u8> {
+        let data = Layout(Vec::new())
+            .put(&PYTH_MAGIC.to_le_bytes()) // magic
+            .put(&PYTH_VERSION.to_le_bytes()) // ver
+            .put(&PYTH_ACCOUNT_TYPE_PRICE.to_le_bytes()) // atype
+            .put(&3312u32.to_le_bytes()) // size
+            .put(&1u32.to_le_bytes()) // ptype, price
+            .put(&expo.to_le_bytes()) // expo
+            .skip(4 + 4) // num, num_qt
+            .skip(8 + 8) // last_slot, valid_slot
+            .skip(24 + 24) // ema_price, ema_conf
+            .put(&timestamp.to_le_bytes()) // timestamp
+            .skip(1 + 1 + 2 + 4) // min_pub, drv2, drv3, drv4
+            .skip(32 + 32) // prod, next
+            .skip(8 + 8 + 8 + 8) // prev_slot, prev_price, prev_conf, prev_timestamp
+            .put(&price.to_le_bytes()) // agg.price
+            .skip(8) // agg.conf
+            .put(&status.to_le_bytes()) // agg.status
+            .skip(4 + 8) // agg.corp_act, agg.pub_slot
+            .skip(32 * 96); // comp
+
+        assert_eq!(data.0.len(), 3312);
+        data.0
+    }
+
+    fn switchboard_account(
+        mantissa: i128,
+        scale: u32,
+        num_success: u32,
+        timestamp: i64,
+    ) -> Vec<u8> {
+        let data = Layout(Vec::new())
+            .put(&SWITCHBOARD_AGGREGATOR_DISCRIMINATOR)
+            .skip(32 + 128 + 32 + 32) // name, metadata, _reserved1, queue_pubkey
+            .skip(4 * 4) // batch size, min oracle & job results, min update delay
+            .skip(8) // start_after
+            .skip(16 + 4) // variance_threshold
+            .skip(8 * 4) // force_report_period, expiration, failure count, next update time
+            .skip(1 + 32) // is_locked, crank_pubkey
+            .put(&num_success.to_le_bytes()) // latest_confirmed_round.num_success
+            .skip(4 + 1 + 8) // num_error, is_closed, round_open_slot
+            .put(&timestamp.to_le_bytes()) // round_open_timestamp
+            .put(&mantissa.to_le_bytes()) // result.mantissa
+            .put(&scale.to_le_bytes()); // result.scale
+
+        assert_eq!(data.0.len(), SWITCHBOARD_ACCOUNT_LEN);
+        data.skip(3851 - SWITCHBOARD_ACCOUNT_LEN).0
+    }
+
+    #[test]
+    fn test_switchboard_discriminator() {
+        // anchor account discriminator, first 8 bytes of sha256("account:<name>")
+        let hash = anchor_lang::solana_program::hash::hash(b"account:AggregatorAccountData");
+        assert_eq!(hash.to_bytes()[..8], SWITCHBOARD_AGGREGATOR_DISCRIMINATOR);
+    }
+
+    #[test]
+    fn test_pyth_price() {
+        // $0.99875 with expo -8
+        let data = pyth_account(99_875_000, -8, PYTH_STATUS_TRADING, 1_000);
+        assert_eq!(
+            load_price(ORACLE_SOURCE_PYTH, &data).unwrap(),
+            OraclePrice {
+                price_bps: 9_987,
+                timestamp: 1_000,
+            }
+        );
+
+        // negative price
+        let data = pyth_account(-1, -8, PYTH_STATUS_TRADING, 1_000);
+        assert_eq!(load_pyth_price(&data).unwrap().price_bps, 0);
+
+        // positive exponent
+        let data = pyth_account(1, 0, PYTH_STATUS_TRADING, 1_000);
+        assert_eq!(load_pyth_price(&data).unwrap().price_bps, 10_000);
+    }
+
+    #[test]
+    fn test_pyth_invalid_account() {
+        let data = pyth_account(100_000_000, -8, PYTH_STATUS_TRADING, 1_000);
+
+        // truncated account
+        assert!(load_pyth_price(&data[..PYTH_ACCOUNT_LEN - 1]).is_err());
+
+        // wrong magic number
+        let mut wrong = data.clone();
+        wrong[0] = 0;
+        assert!(load_pyth_price(&wrong).is_err());
+
+        // not a price account
+        let mut wrong = data.clone();
+        wrong[PYTH_ACCOUNT_TYPE_OFFSET] = 2;
+        assert!(load_pyth_price(&wrong).is_err());
+
+        // switchboard layout
+        assert!(load_switchboard_price(&data).is_err());
+
+        // halted aggregate price
+        let data = pyth_account(100_000_000, -8, 0, 1_000);
+        assert!(load_pyth_price(&data).is_err());
+    }
+
+    #[test]
+    fn test_switchboard_price() {
+        // $1.0002 with scale 4
+        let data = switchboard_account(10_002, 4, 3, 2_000);
+        assert_eq!(
+            load_price(ORACLE_SOURCE_SWITCHBOARD, &data).unwrap(),
+            OraclePrice {
+                price_bps: 10_002,
+                timestamp: 2_000,
+            }
+        );
+
+        // large scale rounds down
+        let data = switchboard_account(999_999_999_999_999_999, 18, 1, 2_000);
+        assert_eq!(load_switchboard_price(&data).unwrap().price_bps, 9_999);
+    }
+
+    #[test]
+    fn test_switchboard_invalid_account() {
+        let data = switchboard_account(10_000, 4, 1, 2_000);
+
+        // truncated account
+        assert!(load_switchboard_price(&data[..SWITCHBOARD_ACCOUNT_LEN - 1]).is_err());
+
+        // wrong discriminator
+        let mut wrong = data.clone();
+        wrong[0] = 0;
+        assert!(load_switchboard_price(&wrong).is_err());
+
+        // pyth layout
+        assert!(load_pyth_price(&data).is_err());
+
+        // no confirmed round
+        let data = switchboard_account(10_000, 4, 0, 2_000);
+        assert!(load_switchboard_price(&data).is_err());
+
+        // unknown source
+        assert!(load_price(ORACLE_SOURCE_NONE, &data).is_err());
+    }
+}
@@ -1,22 +1,58 @@
-use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token::{Mint, TokenAccount};
+use std::mem::size_of;
 
-use crate::{args::*, error::*, states::*};
+use crate::{args::*, constant::*, error::*, event::*, states::*};
 
 //-----------------------------------------------------
 
 /// accounts for [create_admin]
 #[derive(Accounts)]
+#[instruction(args: CreateAdminArgs)]
 pub struct CreateAdmin<'info> {
     /// admin, onering initializer
+    #[account(mut)]
     pub admin: Signer<'info>,
 
     /// 1USD mint, collateral asset
+    #[account(
+        constraint = ousd_mint.mint_authority == COption::Some(ousd_mint_auth.key()) @ OneRingFinanceError::InvalidOusdMintAuthority,
+        constraint = ousd_mint.freeze_authority.is_none()
+            || ousd_mint.freeze_authority == COption::Some(ousd_mint_auth.key()) @ OneRingFinanceError::InvalidOusdFreezeAuthority,
+    )]
     pub ousd_mint: Box<Account<'info, Mint>>,
 
-    /// main state
-    #[account(zero)]
+    /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = args.ousd_mint_auth_bump,
+    )]
+    pub ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// treasury 1USD token, receives fees
+    #[account(
+        constraint = treasury.mint.eq(&ousd_mint.key()) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+
+    /// main state, one per 1USD mint
+    #[account(
+        init,
+        seeds = [
+            STATE_SEED.as_ref(),
+            ousd_mint.key().as_ref(),
+        ],
+        bump = args.state_bump,
+        payer = admin,
+        space = 8 + size_of::<State>(),
+    )]
     pub state: Box<Account<'info, State>>,
+
+    /// system program
+    pub system_program: Program<'info, System>,
 }
 
 /// implementation for [CreateAdmin]
@@ -27,15 +63,44 @@ impl<'info> CreateAdmin<'info> {
 
         self.state.ousd_mint = self.ousd_mint.key();
         self.state.ousd_mint_auth_bump = args.ousd_mint_auth_bump;
+        self.state.state_bump = args.state_bump;
 
         self.state.stable_vault_auth_bump = args.stable_vault_auth_bump;
 
         self.state.deposit_amount = 0;
+        self.state.weighted_deposit_amount = 0;
+        self.state.pending_withdraw_amount = 0;
         self.state.reward_amount = 0;
+        self.state.unclaimed_reward_amount = 0;
         self.state.first_update_time = 0;
         self.state.last_update_time = 0;
+        self.state.reward_index = 0;
+        self.state.queued_redeem_amount = 0;
+        self.state.escrowed_redeem_amount = 0;
+        self.state.treasury = self.treasury.key();
+        self.state.pending_admin = Pubkey::default();
+        self.state.guardian = Pubkey::default();
+        self.state.operator = Pubkey::default();
+        self.state.harvester = Pubkey::default();
+        self.state.timelock_delay = 0;
+        self.state.action_count = 0;
+        self.state.global_cap = 0;
+        self.state.outstanding_amount = 0;
+        self.state.flash_mint_cap = 0;
+        self.state.flash_fee_bps = 0;
+        self.state.share_mint = Pubkey::default();
+        self.state.legacy_state = Pubkey::default();
+        self.state.total_shares = 0;
+        self.state.total_underlying = 0;
 
-        self.state.emergency_flag = false;
+        self.state.pause_flags = 0;
+
+        emit!(CreateAdminEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            ousd_mint: self.ousd_mint.key(),
+            treasury: self.treasury.key(),
+        });
 
         Ok(())
     }
@@ -43,28 +108,120 @@ impl<'info> CreateAdmin<'info> {
 
 //-----------------------------------------------------
 
-/// accounts for [apply_new_admin]
+/// accounts for [propose_admin]
 #[derive(Accounts)]
-pub struct ApplyNewAdmin<'info> {
+pub struct ProposeAdmin<'info> {
     /// admin
     pub admin: Signer<'info>,
 
-    /// new admin
+    /// proposed admin
     pub new_admin: UncheckedAccount<'info>,
 
     /// main state
     #[account(
         mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.timelock_delay == 0 @ OneRingFinanceError::TimelockRequired,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [ProposeAdmin]
+impl<'info> ProposeAdmin<'info> {
+    /// process [propose_admin]
+    pub fn process(&mut self) -> ProgramResult {
+        self.state.pending_admin = self.new_admin.key();
+
+        emit!(ProposeAdminEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            pending_admin: self.new_admin.key(),
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [accept_admin]
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    /// proposed admin, must sign to take over
+    pub pending_admin: Signer<'info>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.pending_admin.eq(pending_admin.key) @ OneRingFinanceError::AccessDenied,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [AcceptAdmin]
+impl<'info> AcceptAdmin<'info> {
+    /// process [accept_admin]
+    pub fn process(&mut self) -> ProgramResult {
+        let old_admin = self.state.admin;
+
+        self.state.admin = self.pending_admin.key();
+        self.state.pending_admin = Pubkey::default();
+
+        emit!(AcceptAdminEvent {
+            old_admin,
+            admin: self.pending_admin.key(),
+            state: self.state.key(),
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [cancel_admin_transfer]
+#[derive(Accounts)]
+pub struct CancelAdminTransfer<'info> {
+    /// admin
+    pub admin: Signer<'info>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
         has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.pending_admin.ne(&Pubkey::default()) @ OneRingFinanceError::NoPendingAdmin,
     )]
     pub state: Box<Account<'info, State>>,
 }
 
-/// implementation for [ApplyNewAdmin]
-impl<'info> ApplyNewAdmin<'info> {
-    /// process [apply_new_admin]
+/// implementation for [CancelAdminTransfer]
+impl<'info> CancelAdminTransfer<'info> {
+    /// process [cancel_admin_transfer]
     pub fn process(&mut self) -> ProgramResult {
-        self.state.admin = self.new_admin.key();
+        let pending_admin = self.state.pending_admin;
+
+        self.state.pending_admin = Pubkey::default();
+
+        emit!(CancelAdminTransferEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            pending_admin,
+        });
 
         Ok(())
     }
@@ -73,15 +230,23 @@ impl<'info> ApplyNewAdmin<'info> {
 //-----------------------------------------------------
 
 /// accounts for [update_state]
+/// guardian can only set more pause flags, admin can also clear them
 #[derive(Accounts)]
+#[instruction(args: UpdateStateArgs)]
 pub struct UpdateState<'info> {
-    /// admin, onering initializer
-    pub admin: Signer<'info>,
+    /// admin or guardian
+    pub authority: Signer<'info>,
 
     /// global state
     #[account(
         mut,
-        has_one = admin @ OneRingFinanceError::AccessDenied,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.is_guardian(authority.key) @ OneRingFinanceError::AccessDenied,
+        constraint = args.pause_flags & state.pause_flags == state.pause_flags || state.admin.eq(authority.key) @ OneRingFinanceError::AccessDenied,
     )]
     pub state: Box<Account<'info, State>>,
 }
@@ -90,7 +255,181 @@ pub struct UpdateState<'info> {
 impl<'info> UpdateState<'info> {
     /// process [update_state]
     pub fn process(&mut self, args: UpdateStateArgs) -> ProgramResult {
-        self.state.emergency_flag = args.emergency_flag;
+        if args.pause_flags & !PAUSE_ALL != 0 {
+            return Err(OneRingFinanceError::InvalidPauseFlags.into());
+        }
+
+        self.state.pause_flags = args.pause_flags;
+
+        emit!(UpdateStateEvent {
+            authority: self.authority.key(),
+            state: self.state.key(),
+            pause_flags: args.pause_flags,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [update_roles]
+#[derive(Accounts)]
+pub struct UpdateRoles<'info> {
+    /// admin
+    pub admin: Signer<'info>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.timelock_delay == 0 @ OneRingFinanceError::TimelockRequired,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [UpdateRoles]
+impl<'info> UpdateRoles<'info> {
+    /// process [update_roles]
+    pub fn process(&mut self, args: UpdateRolesArgs) -> ProgramResult {
+        self.state.guardian = args.guardian;
+        self.state.operator = args.operator;
+        self.state.harvester = args.harvester;
+
+        emit!(UpdateRolesEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            guardian: args.guardian,
+            operator: args.operator,
+            harvester: args.harvester,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [update_treasury]
+#[derive(Accounts)]
+pub struct UpdateTreasury<'info> {
+    /// admin
+    pub admin: Signer<'info>,
+
+    /// treasury 1USD token, receives fees
+    #[account(
+        constraint = treasury.mint.eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.timelock_delay == 0 @ OneRingFinanceError::TimelockRequired,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [UpdateTreasury]
+impl<'info> UpdateTreasury<'info> {
+    /// process [update_treasury]
+    pub fn process(&mut self) -> ProgramResult {
+        self.state.treasury = self.treasury.key();
+
+        emit!(UpdateTreasuryEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            treasury: self.treasury.key(),
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [update_flash_mint]
+#[derive(Accounts)]
+pub struct UpdateFlashMint<'info> {
+    /// admin
+    pub admin: Signer<'info>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.timelock_delay == 0 @ OneRingFinanceError::TimelockRequired,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [UpdateFlashMint]
+impl<'info> UpdateFlashMint<'info> {
+    /// process [update_flash_mint]
+    pub fn process(&mut self, args: UpdateFlashMintArgs) -> ProgramResult {
+        self.state.apply_flash_mint(&args)?;
+
+        emit!(UpdateFlashMintEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            flash_mint_cap: args.flash_mint_cap,
+            flash_fee_bps: args.flash_fee_bps,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [update_withdraw_cooldown]
+#[derive(Accounts)]
+pub struct UpdateWithdrawCooldown<'info> {
+    /// admin
+    pub admin: Signer<'info>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.timelock_delay == 0 @ OneRingFinanceError::TimelockRequired,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [UpdateWithdrawCooldown]
+impl<'info> UpdateWithdrawCooldown<'info> {
+    /// process [update_withdraw_cooldown]
+    pub fn process(&mut self, args: UpdateWithdrawCooldownArgs) -> ProgramResult {
+        self.state.apply_withdraw_cooldown(&args)?;
+
+        emit!(UpdateWithdrawCooldownEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            withdraw_cooldown: args.withdraw_cooldown,
+            early_exit_fee_bps: args.early_exit_fee_bps,
+        });
 
         Ok(())
     }
@@ -2,7 +2,10 @@ use anchor_lang::{prelude::*, solana_program::clock};
 use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
 use std::mem::size_of;
 
-use crate::{args::*, constant::*, error::*, states::*, traits::*};
+use crate::{
+    amount::*, args::*, constant::*, error::*, event::*, processor::check_expiry, states::*,
+    traits::*,
+};
 
 //-----------------------------------------------------
 
@@ -31,7 +34,12 @@ pub struct CreateReserve<'info> {
     /// main state
     #[account(
         mut,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_DEPOSIT) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -43,11 +51,68 @@ pub struct CreateReserve<'info> {
 impl<'info> CreateReserve<'info> {
     /// process [create_reserve]
     pub fn process(&mut self, args: CreateReserveArgs) -> ProgramResult {
-        self.reserve.nonce = args.nonce;
-        self.reserve.deposit_amount = 0;
-        self.reserve.reward_amount = 0;
-        self.reserve.last_update_time = 0;
-        self.reserve.freeze_flag = false;
+        self.reserve.initialize(args.nonce, &self.state);
+
+        emit!(CreateReserveEvent {
+            initializer: self.initializer.key(),
+            reserve: self.reserve.key(),
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [close_reserve]
+#[derive(Accounts)]
+pub struct CloseReserve<'info> {
+    /// user, reserve owner, receives rent
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+
+    /// reserve state
+    #[account(
+        mut,
+        close = initializer,
+        seeds = [
+            initializer.key().as_ref(),
+            RESERVE_SEED.as_ref(),
+            state.key().as_ref(),
+        ],
+        bump = reserve.nonce,
+        constraint = !reserve.freeze_flag @ OneRingFinanceError::ReserveFrozen,
+    )]
+    pub reserve: Box<Account<'info, Reserve>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [CloseReserve]
+impl<'info> CloseReserve<'info> {
+    /// process [close_reserve]
+    /// settle rewards, only an empty reserve can be closed
+    pub fn process(&mut self) -> ProgramResult {
+        // refresh reserve state
+        self.reserve.refresh_reserve(&mut self.state)?;
+
+        if !self.reserve.is_empty() {
+            return Err(OneRingFinanceError::ReserveNotEmpty.into());
+        }
+
+        emit!(CloseReserveEvent {
+            initializer: self.initializer.key(),
+            reserve: self.reserve.key(),
+        });
 
         Ok(())
     }
@@ -57,7 +122,7 @@ impl<'info> CreateReserve<'info> {
 
 /// accounts for [deposit]
 #[derive(Accounts)]
-#[instruction(args: DepositOrWithdrawArgs)]
+#[instruction(args: DepositArgs)]
 pub struct Deposit<'info> {
     /// user, deposit initializer
     pub initializer: Signer<'info>,
@@ -94,7 +159,12 @@ pub struct Deposit<'info> {
     /// main state
     #[account(
         mut,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_DEPOSIT) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -119,25 +189,41 @@ impl<'info> Deposit<'info> {
     }
 
     /// process [deposit]
-    /// deposit 1USD for reward (old stake)
-    pub fn process(&mut self, args: DepositOrWithdrawArgs) -> ProgramResult {
+    /// deposit 1USD for reward (old stake), optionally locked for a boosted reward weight
+    pub fn process(&mut self, args: DepositArgs) -> ProgramResult {
+        let now = clock::Clock::get()?.unix_timestamp;
+
         // initialize first update time
         if self.state.first_update_time == 0 {
-            self.state.first_update_time = clock::Clock::get().unwrap().unix_timestamp;
+            self.state.first_update_time = now;
         }
 
         // refresh reserve state
-        self.reserve.refresh_reserve(&mut self.state);
+        self.reserve.refresh_reserve(&mut self.state)?;
 
-        // accumulate deposit amount of any stable tokens
-        self.reserve.deposit_amount += args.amount;
+        // lock whole deposited principal for the chosen tier
+        self.reserve
+            .apply_lock(&mut self.state, args.lock_tier, now)?;
 
+        // accumulate deposit amount of any stable tokens
         // add stake liquidity, used to calculate rewards
-        self.state.deposit_amount += args.amount;
+        self.reserve.add_deposit(&mut self.state, args.amount)?;
 
         // mint deposit amount of 1USD to initializer
         self.burn_from_initializer(args.amount)?;
 
+        emit!(DepositEvent {
+            initializer: self.initializer.key(),
+            reserve: self.reserve.key(),
+            amount: args.amount,
+            deposit_amount: self.reserve.deposit_amount,
+            reward_amount: self.reserve.reward_amount,
+            total_deposit_amount: self.state.deposit_amount,
+            total_reward_amount: self.state.reward_amount,
+            unlock_time: self.reserve.unlock_time,
+            lock_weight: self.reserve.lock_weight,
+        });
+
         Ok(())
     }
 }
@@ -146,7 +232,7 @@ impl<'info> Deposit<'info> {
 
 /// accounts for [mint_and_deposit]
 #[derive(Accounts)]
-#[instruction(args: DepositOrWithdrawArgs)]
+#[instruction(args: MintOusdArgs)]
 pub struct MintAndDeposit<'info> {
     /// user, reserve initializer
     pub initializer: Signer<'info>,
@@ -186,6 +272,23 @@ pub struct MintAndDeposit<'info> {
     )]
     pub ousd_mint: Box<Account<'info, Mint>>,
 
+    /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
+    pub ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// treasury 1USD token, receives fees
+    #[account(
+        mut,
+        constraint = treasury_ousd_token.key().eq(&state.treasury) @ OneRingFinanceError::InvalidTreasury,
+    )]
+    pub treasury_ousd_token: Box<Account<'info, TokenAccount>>,
+
     /// reserve state
     #[account(
         mut,
@@ -201,6 +304,13 @@ pub struct MintAndDeposit<'info> {
 
     /// market state
     #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
         constraint = !market.lock_flag @ OneRingFinanceError::MarketLocked,
     )]
     pub market: Box<Account<'info, Market>>,
@@ -208,7 +318,12 @@ pub struct MintAndDeposit<'info> {
     /// main state
     #[account(
         mut,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_MINT | PAUSE_DEPOSIT) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -232,43 +347,93 @@ impl<'info> MintAndDeposit<'info> {
         )
     }
 
+    /// mint fee amount of 1USD to treasury
+    pub fn mint_to_treasury(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = MintTo {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.treasury_ousd_token.to_account_info(),
+            authority: self.ousd_mint_auth.to_account_info(),
+        };
+
+        self.state.with_mint_auth_seeds(|mint_seeds| {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[mint_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
     /// process [deposit_and_mint]
     /// deposit 1USD directly for reward (old stake)
-    /// no actual mint needed
-    pub fn process(&mut self, args: DepositOrWithdrawArgs) -> ProgramResult {
+    /// no actual mint needed except for fee
+    pub fn process(
+        &mut self,
+        args: MintOusdArgs,
+        remaining_accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        check_expiry(args.expiry)?;
+
+        // reject mint on depeg or stale price
+        self.market
+            .check_price(remaining_accounts, clock::Clock::get()?.unix_timestamp)?;
+
         // transfer stable token from initializer to vault
         self.transfer_to_vault(args.amount)?;
 
-        // $1USD amount equivalant to stable token amount
-        let ousd_amount = if self.stable_mint.decimals > self.ousd_mint.decimals {
-            args.amount
-                / u64::pow(
-                    10,
-                    (self.stable_mint.decimals - self.ousd_mint.decimals) as u32,
-                )
-        } else if self.stable_mint.decimals < self.ousd_mint.decimals {
-            args.amount
-                * u64::pow(
-                    10,
-                    (self.ousd_mint.decimals - self.stable_mint.decimals) as u32,
-                )
-        } else {
-            args.amount
-        };
+        // $1USD amount equivalant to stable token amount, rounded down
+        let ousd_amount = StableAmount(args.amount)
+            .to_ousd(self.stable_mint.decimals, self.ousd_mint.decimals)?
+            .0;
+
+        // mint fee, sent to treasury
+        let fee_amount = self.market.mint_fee(ousd_amount)?;
+        if ousd_amount - fee_amount < args.min_amount_out {
+            return Err(OneRingFinanceError::SlippageExceeded.into());
+        }
+        if fee_amount > 0 {
+            self.mint_to_treasury(fee_amount)?;
+        }
+
+        // track minted amount against market & global caps, and mint rate limit
+        self.market.add_outstanding(&mut self.state, ousd_amount)?;
+        self.market
+            .add_mint_volume(ousd_amount, clock::Clock::get()?.unix_timestamp)?;
+        let ousd_amount = ousd_amount - fee_amount;
+
+        let now = clock::Clock::get()?.unix_timestamp;
 
         // initialize first update time
         if self.state.first_update_time == 0 {
-            self.state.first_update_time = clock::Clock::get().unwrap().unix_timestamp;
+            self.state.first_update_time = now;
         }
 
         // refresh reserve state
-        self.reserve.refresh_reserve(&mut self.state);
+        self.reserve.refresh_reserve(&mut self.state)?;
 
-        // accumulate deposit amount of any stable tokens
-        self.reserve.deposit_amount += ousd_amount;
+        // join an existing lock, if any
+        self.reserve
+            .apply_lock(&mut self.state, LOCK_TIER_NONE, now)?;
 
+        // accumulate deposit amount of any stable tokens
         // add stake liquidity, used to calculate rewards
-        self.state.deposit_amount += ousd_amount;
+        self.reserve.add_deposit(&mut self.state, ousd_amount)?;
+
+        emit!(MintAndDepositEvent {
+            initializer: self.initializer.key(),
+            market: self.market.key(),
+            reserve: self.reserve.key(),
+            stable_amount: args.amount,
+            ousd_amount: ousd_amount + fee_amount,
+            fee_amount,
+            deposit_amount: self.reserve.deposit_amount,
+            reward_amount: self.reserve.reward_amount,
+            total_deposit_amount: self.state.deposit_amount,
+            total_reward_amount: self.state.reward_amount,
+        });
 
         Ok(())
     }
@@ -291,6 +456,13 @@ pub struct Withdraw<'info> {
     pub ousd_mint: Box<Account<'info, Mint>>,
 
     /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
     pub ousd_mint_auth: UncheckedAccount<'info>,
 
     /// 1USD token
@@ -301,6 +473,13 @@ pub struct Withdraw<'info> {
     )]
     pub initializer_ousd_token: Box<Account<'info, TokenAccount>>,
 
+    /// treasury 1USD token, receives early exit fee
+    #[account(
+        mut,
+        constraint = treasury_ousd_token.key().eq(&state.treasury) @ OneRingFinanceError::InvalidTreasury,
+    )]
+    pub treasury_ousd_token: Box<Account<'info, TokenAccount>>,
+
     /// reserve state
     #[account(
         mut,
@@ -318,7 +497,12 @@ pub struct Withdraw<'info> {
     /// main state
     #[account(
         mut,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_WITHDRAW) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -348,20 +532,246 @@ impl<'info> Withdraw<'info> {
         })
     }
 
+    /// mint early exit fee of 1USD to treasury
+    pub fn mint_to_treasury(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = MintTo {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.treasury_ousd_token.to_account_info(),
+            authority: self.ousd_mint_auth.to_account_info(),
+        };
+
+        self.state.with_mint_auth_seeds(|mint_seeds| {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[mint_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
     /// process [withdraw]
-    /// widthdraw, burn same amount of 1USD
+    /// widthdraw instantly, mint same amount of 1USD less early exit fee
+    /// the fee is minted to treasury
     pub fn process(&mut self, args: DepositOrWithdrawArgs) -> ProgramResult {
+        let now = clock::Clock::get()?.unix_timestamp;
+
+        // locked principal can't be withdrawn until the unlock time
+        if self.reserve.is_locked(now) {
+            return Err(OneRingFinanceError::ReserveLocked.into());
+        }
+
         // refresh reserve state
-        self.reserve.refresh_reserve(&mut self.state);
+        self.reserve.refresh_reserve(&mut self.state)?;
 
-        // reduct deposit amount
-        self.reserve.deposit_amount -= args.amount;
+        // expired lock falls back to 1x weight
+        self.reserve
+            .apply_lock(&mut self.state, LOCK_TIER_NONE, now)?;
 
+        // reduct deposit amount
         // reduct stake liquidity
-        self.state.deposit_amount -= args.amount;
+        self.reserve.sub_deposit(&mut self.state, args.amount)?;
 
-        // mint withdraw amount of 1USD to initializer
-        self.mint_to_initializer(args.amount)?;
+        // mint withdraw amount of 1USD less early exit fee to initializer
+        let fee_amount = self.state.early_exit_fee(args.amount)?;
+        self.mint_to_initializer(args.amount - fee_amount)?;
+
+        // early exit fee, sent to treasury
+        if fee_amount > 0 {
+            self.mint_to_treasury(fee_amount)?;
+        }
+
+        emit!(WithdrawEvent {
+            initializer: self.initializer.key(),
+            reserve: self.reserve.key(),
+            amount: args.amount,
+            fee_amount,
+            deposit_amount: self.reserve.deposit_amount,
+            reward_amount: self.reserve.reward_amount,
+            total_deposit_amount: self.state.deposit_amount,
+            total_reward_amount: self.state.reward_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [request_withdraw]
+#[derive(Accounts)]
+#[instruction(args: DepositOrWithdrawArgs)]
+pub struct RequestWithdraw<'info> {
+    /// user, reserve initializer
+    pub initializer: Signer<'info>,
+
+    /// reserve state
+    #[account(
+        mut,
+        seeds = [
+            initializer.key().as_ref(),
+            RESERVE_SEED.as_ref(),
+            state.key().as_ref(),
+        ],
+        bump = reserve.nonce,
+        constraint = !reserve.freeze_flag @ OneRingFinanceError::ReserveFrozen,
+        constraint = reserve.deposit_amount >= args.amount @ OneRingFinanceError::WithdrawalAmountTooMuch,
+    )]
+    pub reserve: Box<Account<'info, Reserve>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_WITHDRAW) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [RequestWithdraw]
+impl<'info> RequestWithdraw<'info> {
+    /// process [request_withdraw]
+    /// move withdraw amount into the pending bucket, it stops earning rewards
+    pub fn process(&mut self, args: DepositOrWithdrawArgs) -> ProgramResult {
+        let now = clock::Clock::get()?.unix_timestamp;
+
+        // locked principal can't be withdrawn until the unlock time
+        if self.reserve.is_locked(now) {
+            return Err(OneRingFinanceError::ReserveLocked.into());
+        }
+
+        // refresh reserve state
+        self.reserve.refresh_reserve(&mut self.state)?;
+
+        // expired lock falls back to 1x weight
+        self.reserve
+            .apply_lock(&mut self.state, LOCK_TIER_NONE, now)?;
+
+        // reduct deposit amount & stake liquidity, restart the cooldown
+        self.reserve
+            .request_withdraw(&mut self.state, args.amount, now)?;
+
+        emit!(RequestWithdrawEvent {
+            initializer: self.initializer.key(),
+            reserve: self.reserve.key(),
+            amount: args.amount,
+            pending_amount: self.reserve.pending_amount,
+            withdrawable_time: self.reserve.withdrawable_time,
+            deposit_amount: self.reserve.deposit_amount,
+            total_deposit_amount: self.state.deposit_amount,
+            total_pending_withdraw_amount: self.state.pending_withdraw_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [complete_withdraw]
+#[derive(Accounts)]
+pub struct CompleteWithdraw<'info> {
+    /// user, reserve initializer
+    pub initializer: Signer<'info>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        mut,
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
+    pub ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// 1USD token
+    #[account(
+        mut,
+        constraint = initializer_ousd_token.owner.eq(initializer.key) @ OneRingFinanceError::InvalidOusdAccountOwner,
+        constraint = initializer_ousd_token.mint.eq(&ousd_mint.key()) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub initializer_ousd_token: Box<Account<'info, TokenAccount>>,
+
+    /// reserve state
+    #[account(
+        mut,
+        seeds = [
+            initializer.key().as_ref(),
+            RESERVE_SEED.as_ref(),
+            state.key().as_ref(),
+        ],
+        bump = reserve.nonce,
+        constraint = !reserve.freeze_flag @ OneRingFinanceError::ReserveFrozen,
+    )]
+    pub reserve: Box<Account<'info, Reserve>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_WITHDRAW) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [CompleteWithdraw]
+impl<'info> CompleteWithdraw<'info> {
+    /// mint pending amount of 1USD to initializer
+    pub fn mint_to_initializer(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = MintTo {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.initializer_ousd_token.to_account_info(),
+            authority: self.ousd_mint_auth.to_account_info(),
+        };
+
+        self.state.with_mint_auth_seeds(|mint_seeds| {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[mint_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
+    /// process [complete_withdraw]
+    /// mint whole pending amount of 1USD, no fee charged
+    pub fn process(&mut self) -> ProgramResult {
+        let amount = self
+            .reserve
+            .complete_withdraw(&mut self.state, clock::Clock::get()?.unix_timestamp)?;
+
+        // mint pending amount of 1USD to initializer
+        self.mint_to_initializer(amount)?;
+
+        emit!(CompleteWithdrawEvent {
+            initializer: self.initializer.key(),
+            reserve: self.reserve.key(),
+            amount,
+            total_pending_withdraw_amount: self.state.pending_withdraw_amount,
+        });
 
         Ok(())
     }
@@ -383,6 +793,13 @@ pub struct Claim<'info> {
     pub ousd_mint: Box<Account<'info, Mint>>,
 
     /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
     pub ousd_mint_auth: UncheckedAccount<'info>,
 
     /// 1USD token
@@ -409,7 +826,12 @@ pub struct Claim<'info> {
     /// main state
     #[account(
         mut,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_CLAIM) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -443,19 +865,24 @@ impl<'info> Claim<'info> {
     /// claim for rewards
     pub fn process(&mut self, args: DepositOrWithdrawArgs) -> ProgramResult {
         // refresh reserve state
-        self.reserve.refresh_reserve(&mut self.state);
-
-        // check if claim amount less than reward amount
-        if self.reserve.reward_amount < args.amount {
-            return Err(OneRingFinanceError::ClaimAmountTooMuch.into());
-        }
+        self.reserve.refresh_reserve(&mut self.state)?;
 
         // reduct reward amount
-        self.reserve.reward_amount -= args.amount;
+        self.reserve.claim_reward(&mut self.state, args.amount)?;
 
         // mint claim amount of 1USD to initializer
         self.mint_to_initializer(args.amount)?;
 
+        emit!(ClaimEvent {
+            initializer: self.initializer.key(),
+            reserve: self.reserve.key(),
+            amount: args.amount,
+            deposit_amount: self.reserve.deposit_amount,
+            reward_amount: self.reserve.reward_amount,
+            total_deposit_amount: self.state.deposit_amount,
+            total_reward_amount: self.state.reward_amount,
+        });
+
         Ok(())
     }
 }
@@ -466,14 +893,20 @@ impl<'info> Claim<'info> {
 #[derive(Accounts)]
 #[instruction(args: DepositOrWithdrawArgs)]
 pub struct ClaimAndDeposit<'info> {
-    /// user, reserve initializer
+    /// reserve owner or its delegate
+    #[account(
+        constraint = initializer.key().eq(owner.key) || reserve.is_delegate(initializer.key) @ OneRingFinanceError::AccessDenied,
+    )]
     pub initializer: Signer<'info>,
 
+    /// user, reserve owner
+    pub owner: UncheckedAccount<'info>,
+
     /// reserve state
     #[account(
         mut,
         seeds = [
-            initializer.key().as_ref(),
+            owner.key().as_ref(),
             RESERVE_SEED.as_ref(),
             state.key().as_ref(),
         ],
@@ -485,35 +918,243 @@ pub struct ClaimAndDeposit<'info> {
     /// main state
     #[account(
         mut,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_CLAIM | PAUSE_DEPOSIT) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
-
-    /// token program
-    pub token_program: Program<'info, Token>,
 }
 
 /// implementation for [ClaimAndDeposit]
 impl<'info> ClaimAndDeposit<'info> {
     /// process [cliam_and_deposit]
     /// claim and deposit directly, transfer or burn not needed
+    /// a delegate can compound this way, nothing leaves the reserve
     pub fn process(&mut self, args: DepositOrWithdrawArgs) -> ProgramResult {
         // refresh reserve state
-        self.reserve.refresh_reserve(&mut self.state);
-
-        // check if claim amount less than reward amount
-        if self.reserve.reward_amount < args.amount {
-            return Err(OneRingFinanceError::ClaimAmountTooMuch.into());
-        }
+        self.reserve.refresh_reserve(&mut self.state)?;
 
         // reduct reward amount
-        self.reserve.reward_amount -= args.amount;
+        self.reserve.claim_reward(&mut self.state, args.amount)?;
 
-        // accumulate deposit amount of any stable tokens
-        self.reserve.deposit_amount += args.amount;
+        // join an existing lock, if any
+        self.reserve.apply_lock(
+            &mut self.state,
+            LOCK_TIER_NONE,
+            clock::Clock::get()?.unix_timestamp,
+        )?;
 
+        // accumulate deposit amount of any stable tokens
         // add stake liquidity, used to calculate rewards
-        self.state.deposit_amount += args.amount;
+        self.reserve.add_deposit(&mut self.state, args.amount)?;
+
+        emit!(ClaimAndDepositEvent {
+            initializer: self.initializer.key(),
+            owner: self.owner.key(),
+            reserve: self.reserve.key(),
+            amount: args.amount,
+            deposit_amount: self.reserve.deposit_amount,
+            reward_amount: self.reserve.reward_amount,
+            total_deposit_amount: self.state.deposit_amount,
+            total_reward_amount: self.state.reward_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [set_delegate]
+#[derive(Accounts)]
+pub struct SetDelegate<'info> {
+    /// user, reserve owner
+    pub initializer: Signer<'info>,
+
+    /// reserve state
+    #[account(
+        mut,
+        seeds = [
+            initializer.key().as_ref(),
+            RESERVE_SEED.as_ref(),
+            state.key().as_ref(),
+        ],
+        bump = reserve.nonce,
+        constraint = !reserve.freeze_flag @ OneRingFinanceError::ReserveFrozen,
+    )]
+    pub reserve: Box<Account<'info, Reserve>>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [SetDelegate]
+impl<'info> SetDelegate<'info> {
+    /// process [set_delegate]
+    /// delegate can only claim_and_deposit on behalf of the owner
+    pub fn process(&mut self, args: SetDelegateArgs) -> ProgramResult {
+        self.reserve.delegate = args.delegate;
+
+        emit!(SetDelegateEvent {
+            initializer: self.initializer.key(),
+            reserve: self.reserve.key(),
+            delegate: args.delegate,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [revoke_delegate]
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    /// user, reserve owner
+    pub initializer: Signer<'info>,
+
+    /// reserve state, revocable even if frozen
+    #[account(
+        mut,
+        seeds = [
+            initializer.key().as_ref(),
+            RESERVE_SEED.as_ref(),
+            state.key().as_ref(),
+        ],
+        bump = reserve.nonce,
+    )]
+    pub reserve: Box<Account<'info, Reserve>>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [RevokeDelegate]
+impl<'info> RevokeDelegate<'info> {
+    /// process [revoke_delegate]
+    pub fn process(&mut self) -> ProgramResult {
+        self.reserve.delegate = Pubkey::default();
+
+        emit!(RevokeDelegateEvent {
+            initializer: self.initializer.key(),
+            reserve: self.reserve.key(),
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [transfer_reserve]
+#[derive(Accounts)]
+#[instruction(args: TransferReserveArgs)]
+pub struct TransferReserve<'info> {
+    /// user, reserve owner, pays for recipient reserve if needed
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+
+    /// reserve state
+    #[account(
+        mut,
+        seeds = [
+            initializer.key().as_ref(),
+            RESERVE_SEED.as_ref(),
+            state.key().as_ref(),
+        ],
+        bump = reserve.nonce,
+        constraint = !reserve.freeze_flag @ OneRingFinanceError::ReserveFrozen,
+    )]
+    pub reserve: Box<Account<'info, Reserve>>,
+
+    /// recipient wallet, signs so that nobody else can push a lock into its reserve
+    #[account(
+        constraint = recipient.key().ne(initializer.key) @ OneRingFinanceError::SameReserveTransfer,
+    )]
+    pub recipient: Signer<'info>,
+
+    /// recipient reserve state, created if needed
+    #[account(
+        init_if_needed,
+        seeds = [
+            recipient.key().as_ref(),
+            RESERVE_SEED.as_ref(),
+            state.key().as_ref(),
+        ],
+        bump = args.recipient_nonce,
+        payer = initializer,
+        space = 8 + size_of::<Reserve>(),
+        constraint = !recipient_reserve.freeze_flag @ OneRingFinanceError::ReserveFrozen,
+    )]
+    pub recipient_reserve: Box<Account<'info, Reserve>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_DEPOSIT | PAUSE_WITHDRAW) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// system program
+    pub system_program: Program<'info, System>,
+}
+
+/// implementation for [TransferReserve]
+impl<'info> TransferReserve<'info> {
+    /// process [transfer_reserve]
+    /// settle rewards of both reserves, then move deposit & unclaimed rewards to the recipient
+    pub fn process(&mut self, args: TransferReserveArgs) -> ProgramResult {
+        let now = clock::Clock::get()?.unix_timestamp;
+
+        // initialize recipient reserve if just created
+        if !self.recipient_reserve.is_initialized() {
+            self.recipient_reserve
+                .initialize(args.recipient_nonce, &self.state);
+        }
+
+        // refresh reserve states
+        self.reserve.refresh_reserve(&mut self.state)?;
+        self.recipient_reserve.refresh_reserve(&mut self.state)?;
+
+        let deposit_amount = self.reserve.deposit_amount;
+        let reward_amount = self.reserve.reward_amount;
+
+        // move deposit, unclaimed rewards & lock
+        self.reserve
+            .transfer_to(&mut self.recipient_reserve, &mut self.state, now)?;
+
+        emit!(TransferReserveEvent {
+            initializer: self.initializer.key(),
+            recipient: self.recipient.key(),
+            reserve: self.reserve.key(),
+            recipient_reserve: self.recipient_reserve.key(),
+            deposit_amount,
+            reward_amount,
+            unlock_time: self.recipient_reserve.unlock_time,
+            lock_weight: self.recipient_reserve.lock_weight,
+        });
 
         Ok(())
     }
@@ -0,0 +1,276 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::Instruction,
+        sysvar::{self, instructions},
+    },
+};
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+
+use crate::{args::*, constant::*, error::*, event::*, states::*, traits::*};
+
+//-----------------------------------------------------
+
+/// whether instruction is a flash instruction of this program against main state
+fn is_flash_instruction(
+    instruction: &Instruction,
+    discriminator: &[u8; 8],
+    state: &Pubkey,
+) -> bool {
+    instruction.program_id.eq(&crate::ID)
+        && instruction.data.len() >= 8
+        && instruction.data[..8].eq(discriminator)
+        && instruction
+            .accounts
+            .iter()
+            .any(|account| account.pubkey.eq(state))
+}
+
+//-----------------------------------------------------
+
+/// accounts for [flash_mint]
+#[derive(Accounts)]
+#[instruction(args: FlashMintArgs)]
+pub struct FlashMint<'info> {
+    /// flash mint initializer
+    pub initializer: Signer<'info>,
+
+    /// 1USD token, receives flash minted 1USD
+    #[account(
+        mut,
+        constraint = receiver_ousd_token.mint.eq(&ousd_mint.key()) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub receiver_ousd_token: Box<Account<'info, TokenAccount>>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        mut,
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
+    pub ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_MINT) @ OneRingFinanceError::ServiceDisabled,
+        constraint = args.amount <= state.flash_mint_cap @ OneRingFinanceError::FlashMintCapExceeded,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// instructions sysvar, for repay introspection
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [FlashMint]
+impl<'info> FlashMint<'info> {
+    /// mint flash amount of 1USD to receiver
+    pub fn mint_to_receiver(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = MintTo {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.receiver_ousd_token.to_account_info(),
+            authority: self.ousd_mint_auth.to_account_info(),
+        };
+
+        self.state.with_mint_auth_seeds(|mint_seeds| {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[mint_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
+    /// index of a later flash repay instruction of this flash mint
+    pub fn find_flash_repay(&self, flash_mint_index: u16) -> Result<u16> {
+        let instructions_info = self.instructions.to_account_info();
+
+        let mut index = flash_mint_index as usize + 1;
+        while let Ok(instruction) =
+            instructions::load_instruction_at_checked(index, &instructions_info)
+        {
+            if is_flash_instruction(&instruction, &FLASH_REPAY_DISCRIMINATOR, &self.state.key())
+                && FlashRepayArgs::try_from_slice(&instruction.data[8..])
+                    .map(|args| args.flash_mint_index == flash_mint_index)
+                    .unwrap_or(false)
+            {
+                return Ok(index as u16);
+            }
+
+            index += 1;
+        }
+
+        Err(OneRingFinanceError::FlashRepayMissing.into())
+    }
+
+    /// process [flash_mint]
+    /// mint 1USD to be burnt by a flash repay later in the same transaction
+    pub fn process(&mut self, args: FlashMintArgs) -> ProgramResult {
+        let instructions_info = self.instructions.to_account_info();
+        let flash_mint_index = instructions::load_current_index_checked(&instructions_info)?;
+
+        // reject cpi, the current top level instruction must be this flash mint
+        let current = instructions::load_instruction_at_checked(
+            flash_mint_index as usize,
+            &instructions_info,
+        )?;
+        if !is_flash_instruction(&current, &FLASH_MINT_DISCRIMINATOR, &self.state.key()) {
+            return Err(OneRingFinanceError::InvalidFlashMint.into());
+        }
+
+        let flash_repay_index = self.find_flash_repay(flash_mint_index)?;
+
+        // mint flash amount of 1USD to receiver
+        self.mint_to_receiver(args.amount)?;
+
+        emit!(FlashMintEvent {
+            initializer: self.initializer.key(),
+            state: self.state.key(),
+            amount: args.amount,
+            flash_repay_index,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [flash_repay]
+#[derive(Accounts)]
+pub struct FlashRepay<'info> {
+    /// flash repay initializer
+    pub initializer: Signer<'info>,
+
+    /// 1USD token, repays flash minted 1USD & fee
+    #[account(
+        mut,
+        constraint = initializer_ousd_token.owner.eq(initializer.key) @ OneRingFinanceError::InvalidOusdAccountOwner,
+        constraint = initializer_ousd_token.mint.eq(&ousd_mint.key()) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub initializer_ousd_token: Box<Account<'info, TokenAccount>>,
+
+    /// treasury 1USD token, receives fees
+    #[account(
+        mut,
+        constraint = treasury_ousd_token.key().eq(&state.treasury) @ OneRingFinanceError::InvalidTreasury,
+    )]
+    pub treasury_ousd_token: Box<Account<'info, TokenAccount>>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        mut,
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// instructions sysvar, for flash mint introspection
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [FlashRepay]
+impl<'info> FlashRepay<'info> {
+    /// burn flash amount of 1USD from initializer
+    pub fn burn_from_initializer(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = Burn {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.initializer_ousd_token.to_account_info(),
+            authority: self.initializer.to_account_info(),
+        };
+
+        token::burn(
+            CpiContext::new(self.token_program.to_account_info(), cpi_accounts),
+            amount,
+        )
+    }
+
+    /// transfer fee amount of 1USD from initializer to treasury
+    pub fn transfer_to_treasury(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = Transfer {
+            from: self.initializer_ousd_token.to_account_info(),
+            to: self.treasury_ousd_token.to_account_info(),
+            authority: self.initializer.to_account_info(),
+        };
+
+        token::transfer(
+            CpiContext::new(self.token_program.to_account_info(), cpi_accounts),
+            amount,
+        )
+    }
+
+    /// process [flash_repay]
+    /// burn 1USD flash minted by an earlier instruction, fee is sent to treasury
+    pub fn process(&mut self, args: FlashRepayArgs) -> ProgramResult {
+        let instructions_info = self.instructions.to_account_info();
+        let current_index = instructions::load_current_index_checked(&instructions_info)?;
+        if args.flash_mint_index >= current_index {
+            return Err(OneRingFinanceError::InvalidFlashMint.into());
+        }
+
+        // flash minted amount
+        let flash_mint = instructions::load_instruction_at_checked(
+            args.flash_mint_index as usize,
+            &instructions_info,
+        )?;
+        if !is_flash_instruction(&flash_mint, &FLASH_MINT_DISCRIMINATOR, &self.state.key()) {
+            return Err(OneRingFinanceError::InvalidFlashMint.into());
+        }
+        let amount = FlashMintArgs::try_from_slice(&flash_mint.data[8..])
+            .map_err(|_| OneRingFinanceError::InvalidFlashMint)?
+            .amount;
+
+        // burn flash amount of 1USD from initializer
+        self.burn_from_initializer(amount)?;
+
+        // transfer fee amount of 1USD from initializer to treasury
+        let fee_amount = self.state.flash_fee(amount)?;
+        if fee_amount > 0 {
+            self.transfer_to_treasury(fee_amount)?;
+        }
+
+        emit!(FlashRepayEvent {
+            initializer: self.initializer.key(),
+            state: self.state.key(),
+            amount,
+            fee_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
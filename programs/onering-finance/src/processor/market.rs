@@ -1,11 +1,13 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use std::mem::size_of;
 
-use crate::{args::*, constant::*, error::*, states::*};
+use crate::{args::*, constant::*, error::*, event::*, states::*, traits::*};
 
 //-----------------------------------------------------
 
 /// accounts for [create_market]
+/// not timelocked, a new market starts locked while timelock is enabled and only a queued update unlocks it
 #[derive(Accounts)]
 #[instruction(args: CreateMarketArgs)]
 pub struct CreateMarket<'info> {
@@ -41,12 +43,27 @@ pub struct CreateMarket<'info> {
     )]
     pub stable_vault_auth: UncheckedAccount<'info>,
 
-    /// market state
-    #[account(zero)]
+    /// market state, one per stable mint
+    #[account(
+        init,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            stable_mint.key().as_ref(),
+        ],
+        bump = args.market_bump,
+        payer = admin,
+        space = 8 + size_of::<Market>(),
+    )]
     pub market: Box<Account<'info, Market>>,
 
     /// main state
     #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
         has_one = admin @ OneRingFinanceError::AccessDenied,
     )]
     pub state: Box<Account<'info, State>>,
@@ -68,10 +85,496 @@ impl<'info> CreateMarket<'info> {
     pub fn process(&mut self, args: CreateMarketArgs) -> ProgramResult {
         self.market.stable_mint = self.stable_mint.key();
         self.market.stable_vault_bump = args.stable_vault_bump;
+        self.market.market_bump = args.market_bump;
 
         self.market.withdrawal_liq = 0;
 
-        self.market.lock_flag = false;
+        // markets are locked while timelock is enabled, only a queued update unlocks them
+        self.market.lock_flag = self.state.timelock_delay > 0;
+
+        self.market.queue_head = 0;
+        self.market.queue_tail = 0;
+        self.market.queued_amount = 0;
+        self.market.escrowed_amount = 0;
+
+        self.market.mint_fee_bps = 0;
+        self.market.redeem_fee_bps = 0;
+        self.market.swap_fee_bps = 0;
+
+        self.market.supply_cap = 0;
+        self.market.outstanding_amount = 0;
+
+        self.market.rate_limit_window = 0;
+        self.market.mint_limit = 0;
+        self.market.redeem_limit = 0;
+        self.market.mint_volume = 0;
+        self.market.redeem_volume = 0;
+        self.market.volume_update_time = 0;
+
+        self.market.oracle = Pubkey::default();
+        self.market.oracle_source = ORACLE_SOURCE_NONE;
+        self.market.min_price_bps = 0;
+        self.market.max_price_age = 0;
+
+        emit!(CreateMarketEvent {
+            admin: self.admin.key(),
+            market: self.market.key(),
+            stable_mint: self.stable_mint.key(),
+            stable_vault: self.stable_vault.key(),
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [update_market]
+#[derive(Accounts)]
+pub struct UpdateMarket<'info> {
+    /// admin
+    pub admin: Signer<'info>,
+
+    /// market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.timelock_delay == 0 @ OneRingFinanceError::TimelockRequired,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [UpdateMarket]
+impl<'info> UpdateMarket<'info> {
+    /// process [update_market]
+    /// lock or unlock market, set or increment withdrawal liquidity
+    pub fn process(&mut self, args: UpdateMarketArgs) -> ProgramResult {
+        self.market.apply_update(&args)?;
+
+        emit!(UpdateMarketEvent {
+            admin: self.admin.key(),
+            market: self.market.key(),
+            lock_flag: self.market.lock_flag,
+            withdrawal_liq: self.market.withdrawal_liq,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [lock_market]
+/// guardian can only lock a market, admin can also unlock it unless timelock is enabled
+#[derive(Accounts)]
+#[instruction(args: LockMarketArgs)]
+pub struct LockMarket<'info> {
+    /// admin or guardian
+    pub authority: Signer<'info>,
+
+    /// market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.is_guardian(authority.key) @ OneRingFinanceError::AccessDenied,
+        constraint = args.lock_flag || state.admin.eq(authority.key) @ OneRingFinanceError::AccessDenied,
+        constraint = args.lock_flag || state.timelock_delay == 0 @ OneRingFinanceError::TimelockRequired,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [LockMarket]
+impl<'info> LockMarket<'info> {
+    /// process [lock_market]
+    pub fn process(&mut self, args: LockMarketArgs) -> ProgramResult {
+        self.market.lock_flag = args.lock_flag;
+
+        emit!(LockMarketEvent {
+            authority: self.authority.key(),
+            market: self.market.key(),
+            lock_flag: args.lock_flag,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [update_caps]
+#[derive(Accounts)]
+pub struct UpdateCaps<'info> {
+    /// admin
+    pub admin: Signer<'info>,
+
+    /// market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.timelock_delay == 0 @ OneRingFinanceError::TimelockRequired,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [UpdateCaps]
+impl<'info> UpdateCaps<'info> {
+    /// process [update_caps]
+    /// caps only bound new mints, outstanding amount above a lowered cap is kept
+    pub fn process(&mut self, args: UpdateCapsArgs) -> ProgramResult {
+        self.market.supply_cap = args.supply_cap;
+        self.state.global_cap = args.global_cap;
+
+        emit!(UpdateCapsEvent {
+            admin: self.admin.key(),
+            market: self.market.key(),
+            supply_cap: args.supply_cap,
+            global_cap: args.global_cap,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [update_rate_limits]
+#[derive(Accounts)]
+pub struct UpdateRateLimits<'info> {
+    /// admin
+    pub admin: Signer<'info>,
+
+    /// market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.timelock_delay == 0 @ OneRingFinanceError::TimelockRequired,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [UpdateRateLimits]
+impl<'info> UpdateRateLimits<'info> {
+    /// process [update_rate_limits]
+    pub fn process(&mut self, args: UpdateRateLimitsArgs) -> ProgramResult {
+        self.market.apply_rate_limits(&args)?;
+
+        emit!(UpdateRateLimitsEvent {
+            admin: self.admin.key(),
+            market: self.market.key(),
+            window: args.window,
+            mint_limit: args.mint_limit,
+            redeem_limit: args.redeem_limit,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [update_oracle]
+#[derive(Accounts)]
+pub struct UpdateOracle<'info> {
+    /// admin
+    pub admin: Signer<'info>,
+
+    /// market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.timelock_delay == 0 @ OneRingFinanceError::TimelockRequired,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [UpdateOracle]
+impl<'info> UpdateOracle<'info> {
+    /// process [update_oracle]
+    pub fn process(&mut self, args: UpdateOracleArgs) -> ProgramResult {
+        self.market.apply_oracle(&args)?;
+
+        emit!(UpdateOracleEvent {
+            admin: self.admin.key(),
+            market: self.market.key(),
+            oracle: self.market.oracle,
+            oracle_source: self.market.oracle_source,
+            min_price_bps: self.market.min_price_bps,
+            max_price_age: self.market.max_price_age,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [update_fees]
+#[derive(Accounts)]
+#[instruction(args: UpdateFeesArgs)]
+pub struct UpdateFees<'info> {
+    /// admin
+    pub admin: Signer<'info>,
+
+    /// market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.timelock_delay == 0 @ OneRingFinanceError::TimelockRequired,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [UpdateFees]
+impl<'info> UpdateFees<'info> {
+    /// process [update_fees]
+    /// update mint, redeem & swap fees of a market
+    pub fn process(&mut self, args: UpdateFeesArgs) -> ProgramResult {
+        self.market.apply_fees(&args)?;
+
+        emit!(UpdateFeesEvent {
+            admin: self.admin.key(),
+            market: self.market.key(),
+            mint_fee_bps: args.mint_fee_bps,
+            redeem_fee_bps: args.redeem_fee_bps,
+            swap_fee_bps: args.swap_fee_bps,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [close_market]
+/// not timelocked, only a market backing no 1USD is closed and its leftover balance goes to
+/// the treasury, which only changes through a queued action while timelock is enabled
+#[derive(Accounts)]
+pub struct CloseMarket<'info> {
+    /// admin, receives rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// stable vault, leftover balance is swept to treasury and the vault is closed along with the market
+    #[account(
+        mut,
+        seeds = [
+            market.stable_mint.key().as_ref(),
+            STABLE_VAULT_SEED.as_ref(),
+            market.key().as_ref()
+        ],
+        bump = market.stable_vault_bump,
+    )]
+    pub stable_vault: Box<Account<'info, TokenAccount>>,
+
+    /// stable vault authority
+    #[account(
+        seeds = [
+            STABLE_VAULT_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.stable_vault_auth_bump,
+    )]
+    pub stable_vault_auth: UncheckedAccount<'info>,
+
+    /// market state
+    #[account(
+        mut,
+        close = admin,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+        constraint = market.outstanding_amount == 0 @ OneRingFinanceError::MarketNotEmpty,
+        constraint = market.queue_head == market.queue_tail @ OneRingFinanceError::MarketNotEmpty,
+        constraint = market.escrowed_amount == 0 @ OneRingFinanceError::MarketNotEmpty,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// treasury 1USD token
+    #[account(
+        constraint = treasury_ousd_token.key().eq(&state.treasury) @ OneRingFinanceError::InvalidTreasury,
+    )]
+    pub treasury_ousd_token: Box<Account<'info, TokenAccount>>,
+
+    /// treasury stable token, of the same owner as treasury 1USD token, receives leftover vault balance
+    #[account(
+        mut,
+        constraint = treasury_stable_token.owner.eq(&treasury_ousd_token.owner) @ OneRingFinanceError::InvalidTreasury,
+        constraint = treasury_stable_token.mint.eq(&market.stable_mint) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub treasury_stable_token: Box<Account<'info, TokenAccount>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [CloseMarket]
+impl<'info> CloseMarket<'info> {
+    /// transfer leftover stable token from vault to treasury
+    pub fn transfer_to_treasury(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = Transfer {
+            from: self.stable_vault.to_account_info(),
+            to: self.treasury_stable_token.to_account_info(),
+            authority: self.stable_vault_auth.to_account_info(),
+        };
+
+        self.state.with_vault_auth_seeds(|auth_seeds| {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[auth_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
+    /// close stable vault, rent is returned to admin
+    pub fn close_stable_vault(&self) -> ProgramResult {
+        let cpi_accounts = CloseAccount {
+            account: self.stable_vault.to_account_info(),
+            destination: self.admin.to_account_info(),
+            authority: self.stable_vault_auth.to_account_info(),
+        };
+
+        self.state.with_vault_auth_seeds(|auth_seeds| {
+            token::close_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                cpi_accounts,
+                &[auth_seeds],
+            ))
+        })
+    }
+
+    /// process [close_market]
+    /// close an empty market and its vault, no 1USD is backed by it nor queued or escrowed for redemption;
+    /// the vault balance is not checked since anyone can send dust to it
+    pub fn process(&mut self) -> ProgramResult {
+        // sweep leftover stable token to treasury
+        let swept_amount = self.stable_vault.amount;
+        if swept_amount > 0 {
+            self.transfer_to_treasury(swept_amount)?;
+        }
+
+        // close stable vault, rent is returned to admin
+        self.close_stable_vault()?;
+
+        emit!(CloseMarketEvent {
+            admin: self.admin.key(),
+            market: self.market.key(),
+            stable_mint: self.market.stable_mint,
+            swept_amount,
+        });
 
         Ok(())
     }
@@ -0,0 +1,579 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token::{self, CloseAccount, Mint, SetAuthority, Token, TokenAccount, Transfer};
+use spl_token::instruction::AuthorityType;
+use std::mem::size_of;
+
+use crate::{amount::*, args::*, constant::*, error::*, event::*, states::*};
+
+//-----------------------------------------------------
+
+/// deserialize a legacy keypair account of type `T`,
+/// fields appended since its layout are zero initialized
+fn load_legacy<T: AccountDeserialize>(account: &AccountInfo) -> Result<T> {
+    let data = account.try_borrow_data()?;
+    let mut buf = vec![0u8; 8 + size_of::<T>()];
+    let len = data.len().min(buf.len());
+    buf[..len].copy_from_slice(&data[..len]);
+
+    Ok(T::try_deserialize(&mut buf.as_slice())?)
+}
+
+/// pause flags of a legacy emergency flag, which disabled everything
+fn legacy_pause_flags(emergency_flag: u8) -> u8 {
+    if emergency_flag != 0 {
+        PAUSE_ALL
+    } else {
+        0
+    }
+}
+
+/// close a migrated legacy account, rent is refunded to `receiver`
+fn close_legacy<'info>(
+    account: &AccountInfo<'info>,
+    receiver: &AccountInfo<'info>,
+) -> ProgramResult {
+    let lamports = receiver
+        .lamports()
+        .checked_add(account.lamports())
+        .ok_or(OneRingFinanceError::MathOverflow)?;
+
+    **receiver.try_borrow_mut_lamports()? = lamports;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.try_borrow_mut_data()?.fill(0);
+
+    Ok(())
+}
+
+//-----------------------------------------------------
+
+/// accounts for [migrate_state]
+#[derive(Accounts)]
+#[instruction(args: CreateAdminArgs)]
+pub struct MigrateState<'info> {
+    /// admin of legacy main state, pays for main state
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// legacy keypair main state, closed once migrated
+    #[account(
+        mut,
+        owner = crate::ID,
+    )]
+    pub legacy_state: UncheckedAccount<'info>,
+
+    /// legacy 1USD mint authority, derived from legacy main state
+    pub legacy_ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        mut,
+        constraint = ousd_mint.mint_authority == COption::Some(legacy_ousd_mint_auth.key()) @ OneRingFinanceError::InvalidOusdMintAuthority,
+        constraint = ousd_mint.freeze_authority.is_none()
+            || ousd_mint.freeze_authority == COption::Some(legacy_ousd_mint_auth.key()) @ OneRingFinanceError::InvalidOusdFreezeAuthority,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = args.ousd_mint_auth_bump,
+    )]
+    pub ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// treasury 1USD token, receives fees
+    #[account(
+        constraint = treasury.mint.eq(&ousd_mint.key()) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub treasury: Box<Account<'info, TokenAccount>>,
+
+    /// main state, one per 1USD mint
+    #[account(
+        init,
+        seeds = [
+            STATE_SEED.as_ref(),
+            ousd_mint.key().as_ref(),
+        ],
+        bump = args.state_bump,
+        payer = admin,
+        space = 8 + size_of::<State>(),
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+
+    /// system program
+    pub system_program: Program<'info, System>,
+}
+
+/// implementation for [MigrateState]
+impl<'info> MigrateState<'info> {
+    /// hand 1USD mint & freeze authorities over from legacy to main state
+    pub fn set_ousd_mint_auth(&self, legacy_ousd_mint_auth_bump: u8) -> ProgramResult {
+        let legacy_state = self.legacy_state.key();
+        let legacy_mint_seeds: &[&[u8]] = &[
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            legacy_state.as_ref(),
+            &[legacy_ousd_mint_auth_bump],
+        ];
+
+        let mut authority_types = vec![AuthorityType::MintTokens];
+        if self.ousd_mint.freeze_authority.is_some() {
+            authority_types.push(AuthorityType::FreezeAccount);
+        }
+
+        for authority_type in authority_types {
+            let cpi_accounts = SetAuthority {
+                account_or_mint: self.ousd_mint.to_account_info(),
+                current_authority: self.legacy_ousd_mint_auth.to_account_info(),
+            };
+
+            token::set_authority(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[legacy_mint_seeds],
+                ),
+                authority_type,
+                Some(self.ousd_mint_auth.key()),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// process [migrate_state]
+    /// copy legacy keypair main state to its PDA, new fields are zero initialized
+    /// PDAs derived from main state move as well; markets, vaults and reserves are migrated next
+    pub fn process(&mut self, args: CreateAdminArgs) -> ProgramResult {
+        let mut state = load_legacy::<State>(&self.legacy_state)?;
+        if state.admin != self.admin.key() {
+            return Err(OneRingFinanceError::AccessDenied.into());
+        }
+        if state.ousd_mint != self.ousd_mint.key() {
+            return Err(OneRingFinanceError::InvalidOusdMint.into());
+        }
+
+        let legacy_ousd_mint_auth_bump = state.ousd_mint_auth_bump;
+
+        state.state_bump = args.state_bump;
+        state.ousd_mint_auth_bump = args.ousd_mint_auth_bump;
+        state.stable_vault_auth_bump = args.stable_vault_auth_bump;
+        state.legacy_state = self.legacy_state.key();
+        state.treasury = self.treasury.key();
+
+        // legacy emergency flag is read as pause flags
+        state.pause_flags = legacy_pause_flags(state.pause_flags);
+
+        // legacy reserves are weighted 1x
+        state.weighted_deposit_amount = state.deposit_amount;
+
+        self.state.set_inner(state);
+
+        self.set_ousd_mint_auth(legacy_ousd_mint_auth_bump)?;
+
+        close_legacy(&self.legacy_state, &self.admin)?;
+
+        emit!(MigrateStateEvent {
+            admin: self.admin.key(),
+            legacy_state: self.legacy_state.key(),
+            state: self.state.key(),
+            ousd_mint: self.ousd_mint.key(),
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [migrate_market]
+#[derive(Accounts)]
+#[instruction(args: CreateMarketArgs)]
+pub struct MigrateMarket<'info> {
+    /// admin, pays for market state & stable vault
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// stable mint
+    pub stable_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD mint
+    #[account(
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// legacy keypair market state, closed once migrated
+    #[account(
+        mut,
+        owner = crate::ID,
+    )]
+    pub legacy_market: UncheckedAccount<'info>,
+
+    /// legacy stable vault, emptied and closed once migrated
+    #[account(
+        mut,
+        constraint = legacy_stable_vault.owner.eq(legacy_stable_vault_auth.key) @ OneRingFinanceError::InvalidStableVault,
+    )]
+    pub legacy_stable_vault: Box<Account<'info, TokenAccount>>,
+
+    /// legacy stable vault authority, derived from legacy main state
+    pub legacy_stable_vault_auth: UncheckedAccount<'info>,
+
+    /// stable vault
+    #[account(
+        init,
+        seeds = [
+            stable_mint.key().as_ref(),
+            STABLE_VAULT_SEED.as_ref(),
+            market.key().as_ref()
+        ],
+        bump = args.stable_vault_bump,
+        payer = admin,
+        token::mint = stable_mint,
+        token::authority = stable_vault_auth,
+    )]
+    pub stable_vault: Box<Account<'info, TokenAccount>>,
+
+    /// stable vault authority
+    #[account(
+        seeds = [
+            STABLE_VAULT_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.stable_vault_auth_bump,
+    )]
+    pub stable_vault_auth: UncheckedAccount<'info>,
+
+    /// market state, one per stable mint
+    #[account(
+        init,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            stable_mint.key().as_ref(),
+        ],
+        bump = args.market_bump,
+        payer = admin,
+        space = 8 + size_of::<Market>(),
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state, migrated from a legacy main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.legacy_state != Pubkey::default() @ OneRingFinanceError::AccessDenied,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+
+    /// system program
+    pub system_program: Program<'info, System>,
+
+    /// rent var
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// implementation for [MigrateMarket]
+impl<'info> MigrateMarket<'info> {
+    /// move legacy vault balance to stable vault, and close legacy vault
+    pub fn move_legacy_vault(&self, legacy_stable_vault_auth_bump: u8) -> ProgramResult {
+        let legacy_vault_seeds: &[&[u8]] = &[
+            STABLE_VAULT_SEED.as_ref(),
+            self.state.legacy_state.as_ref(),
+            &[legacy_stable_vault_auth_bump],
+        ];
+
+        let cpi_accounts = Transfer {
+            from: self.legacy_stable_vault.to_account_info(),
+            to: self.stable_vault.to_account_info(),
+            authority: self.legacy_stable_vault_auth.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                cpi_accounts,
+                &[legacy_vault_seeds],
+            ),
+            self.legacy_stable_vault.amount,
+        )?;
+
+        let cpi_accounts = CloseAccount {
+            account: self.legacy_stable_vault.to_account_info(),
+            destination: self.admin.to_account_info(),
+            authority: self.legacy_stable_vault_auth.to_account_info(),
+        };
+        token::close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            &[legacy_vault_seeds],
+        ))
+    }
+
+    /// process [migrate_market]
+    /// copy legacy keypair market state to its PDA and move its vault, new fields are zero initialized
+    /// only the vault balance is moved, strategy positions must be unwound to the vault beforehand
+    pub fn process(&mut self, args: CreateMarketArgs) -> ProgramResult {
+        let (legacy_stable_vault_auth, legacy_stable_vault_auth_bump) =
+            Pubkey::find_program_address(
+                &[STABLE_VAULT_SEED.as_ref(), self.state.legacy_state.as_ref()],
+                &crate::ID,
+            );
+        if legacy_stable_vault_auth != self.legacy_stable_vault_auth.key() {
+            return Err(OneRingFinanceError::InvalidStableVault.into());
+        }
+
+        let mut market = load_legacy::<Market>(&self.legacy_market)?;
+        if market.stable_mint != self.stable_mint.key() {
+            return Err(OneRingFinanceError::InvalidStableMint.into());
+        }
+
+        // legacy vault is derived from legacy market, which binds the market to legacy main state
+        let legacy_stable_vault = Pubkey::create_program_address(
+            &[
+                self.stable_mint.key().as_ref(),
+                STABLE_VAULT_SEED.as_ref(),
+                self.legacy_market.key().as_ref(),
+                &[market.stable_vault_bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| OneRingFinanceError::InvalidStableVault)?;
+        if legacy_stable_vault != self.legacy_stable_vault.key() {
+            return Err(OneRingFinanceError::InvalidStableVault.into());
+        }
+
+        market.market_bump = args.market_bump;
+        market.stable_vault_bump = args.stable_vault_bump;
+
+        // legacy vault balance backs outstanding 1USD, so that the market is not empty
+        let outstanding_amount = StableAmount(self.legacy_stable_vault.amount)
+            .to_ousd(self.stable_mint.decimals, self.ousd_mint.decimals)?
+            .0;
+        market.outstanding_amount = outstanding_amount;
+        self.state.outstanding_amount = self
+            .state
+            .outstanding_amount
+            .checked_add(outstanding_amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        self.market.set_inner(market);
+
+        let amount = self.legacy_stable_vault.amount;
+        self.move_legacy_vault(legacy_stable_vault_auth_bump)?;
+
+        close_legacy(&self.legacy_market, &self.admin)?;
+
+        emit!(MigrateMarketEvent {
+            admin: self.admin.key(),
+            legacy_market: self.legacy_market.key(),
+            market: self.market.key(),
+            stable_mint: self.stable_mint.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [migrate_reserve]
+#[derive(Accounts)]
+#[instruction(args: MigrateReserveArgs)]
+pub struct MigrateReserve<'info> {
+    /// user, reserve owner, pays for reserve state
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+
+    /// legacy reserve state, derived from legacy main state, closed once migrated
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [
+            initializer.key().as_ref(),
+            RESERVE_SEED.as_ref(),
+            state.legacy_state.as_ref(),
+        ],
+        bump,
+    )]
+    pub legacy_reserve: UncheckedAccount<'info>,
+
+    /// reserve state
+    #[account(
+        init,
+        seeds = [
+            initializer.key().as_ref(),
+            RESERVE_SEED.as_ref(),
+            state.key().as_ref(),
+        ],
+        bump = args.nonce,
+        payer = initializer,
+        space = 8 + size_of::<Reserve>(),
+    )]
+    pub reserve: Box<Account<'info, Reserve>>,
+
+    /// main state, migrated from a legacy main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.legacy_state != Pubkey::default() @ OneRingFinanceError::AccessDenied,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// system program
+    pub system_program: Program<'info, System>,
+}
+
+/// implementation for [MigrateReserve]
+impl<'info> MigrateReserve<'info> {
+    /// process [migrate_reserve]
+    /// copy legacy reserve to its PDA, checkpointed at the reward index of main state migration
+    pub fn process(&mut self, args: MigrateReserveArgs) -> ProgramResult {
+        let mut reserve = load_legacy::<Reserve>(&self.legacy_reserve)?;
+
+        reserve.nonce = args.nonce;
+
+        // legacy deposits are counted since main state migration, from a zero reward index
+        reserve.reward_index = 0;
+
+        // legacy deposits are not locked, weighted 1x
+        reserve.unlock_time = 0;
+        reserve.lock_weight = LOCK_WEIGHT_BASE;
+
+        // legacy rewards are still owed
+        self.state.unclaimed_reward_amount = self
+            .state
+            .unclaimed_reward_amount
+            .checked_add(reserve.reward_amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        self.reserve.set_inner(reserve);
+
+        close_legacy(&self.legacy_reserve, &self.initializer)?;
+
+        emit!(MigrateReserveEvent {
+            initializer: self.initializer.key(),
+            legacy_reserve: self.legacy_reserve.key(),
+            reserve: self.reserve.key(),
+            deposit_amount: self.reserve.deposit_amount,
+            reward_amount: self.reserve.reward_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[test]
+    fn test_load_legacy_state() {
+        let admin = Pubkey::new_unique();
+        let ousd_mint = Pubkey::new_unique();
+
+        // deployed main state layout
+        let mut data = State::discriminator().to_vec();
+        data.extend_from_slice(admin.as_ref());
+        data.extend_from_slice(ousd_mint.as_ref());
+        data.extend_from_slice(&[254, 253]);
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data.extend_from_slice(&10u64.to_le_bytes());
+        data.extend_from_slice(&100i64.to_le_bytes());
+        data.extend_from_slice(&200i64.to_le_bytes());
+        data.push(0);
+
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+
+        let state = load_legacy::<State>(&account).unwrap();
+        assert_eq!(state.admin, admin);
+        assert_eq!(state.ousd_mint, ousd_mint);
+        assert_eq!(state.ousd_mint_auth_bump, 254);
+        assert_eq!(state.stable_vault_auth_bump, 253);
+        assert_eq!(state.deposit_amount, 1_000);
+        assert_eq!(state.reward_amount, 10);
+        assert_eq!(state.first_update_time, 100);
+        assert_eq!(state.last_update_time, 200);
+        assert_eq!(state.pause_flags, 0);
+        assert_eq!(state.reward_index, 0);
+        assert_eq!(state.legacy_state, Pubkey::default());
+
+        // not a main state
+        let mut data = Market::discriminator().to_vec();
+        data.resize(64, 0);
+        let mut lamports = 0;
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+        assert!(load_legacy::<State>(&account).is_err());
+    }
+
+    #[test]
+    fn test_legacy_emergency_flag() {
+        assert_eq!(legacy_pause_flags(0), 0);
+        assert_eq!(legacy_pause_flags(1), PAUSE_ALL);
+
+        // emergency flag is the last field of deployed main state layout
+        let mut data = State::discriminator().to_vec();
+        data.resize(8 + 32 + 32 + 1 + 1 + 8 * 4, 0);
+        data.push(1);
+
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+            0,
+        );
+
+        // read as mint only, unless migrated
+        let mut state = load_legacy::<State>(&account).unwrap();
+        assert_eq!(state.pause_flags, PAUSE_MINT);
+        state.pause_flags = legacy_pause_flags(state.pause_flags);
+        assert!(state.is_paused(PAUSE_WITHDRAW));
+        assert_eq!(state.pause_flags, PAUSE_ALL);
+    }
+}
@@ -1,13 +1,26 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::clock};
 use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
 
-use crate::{args::*, constant::*, error::*, states::*, traits::*};
+use crate::{amount::*, args::*, constant::*, error::*, event::*, states::*, traits::*};
+
+//-----------------------------------------------------
+
+/// reject if the transaction is executed after expiry
+pub fn check_expiry(expiry: Option<i64>) -> ProgramResult {
+    if let Some(expiry) = expiry {
+        if clock::Clock::get()?.unix_timestamp > expiry {
+            return Err(OneRingFinanceError::Expired.into());
+        }
+    }
+
+    Ok(())
+}
 
 //-----------------------------------------------------
 
 /// accounts for [mint_ousd]
 #[derive(Accounts)]
-#[instruction(args: DepositOrWithdrawArgs)]
+#[instruction(args: MintOusdArgs)]
 pub struct MintOusd<'info> {
     /// user, mint initializer
     pub initializer: Signer<'info>,
@@ -48,6 +61,13 @@ pub struct MintOusd<'info> {
     pub ousd_mint: Box<Account<'info, Mint>>,
 
     /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
     pub ousd_mint_auth: UncheckedAccount<'info>,
 
     /// 1USD token
@@ -58,15 +78,35 @@ pub struct MintOusd<'info> {
     )]
     pub initializer_ousd_token: Box<Account<'info, TokenAccount>>,
 
+    /// treasury 1USD token, receives fees
+    #[account(
+        mut,
+        constraint = treasury_ousd_token.key().eq(&state.treasury) @ OneRingFinanceError::InvalidTreasury,
+    )]
+    pub treasury_ousd_token: Box<Account<'info, TokenAccount>>,
+
     /// market state
     #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
         constraint = !market.lock_flag @ OneRingFinanceError::MarketLocked,
     )]
     pub market: Box<Account<'info, Market>>,
 
     /// main state
     #[account(
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_MINT) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -110,31 +150,75 @@ impl<'info> MintOusd<'info> {
         })
     }
 
+    /// mint fee amount of 1USD to treasury
+    pub fn mint_to_treasury(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = MintTo {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.treasury_ousd_token.to_account_info(),
+            authority: self.ousd_mint_auth.to_account_info(),
+        };
+
+        self.state.with_mint_auth_seeds(|mint_seeds| {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[mint_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
     /// process [mint]
     /// deposit to the market
-    pub fn process(&mut self, args: DepositOrWithdrawArgs) -> ProgramResult {
+    pub fn process(
+        &mut self,
+        args: MintOusdArgs,
+        remaining_accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        check_expiry(args.expiry)?;
+
+        // reject mint on depeg or stale price, redemptions stay available
+        self.market
+            .check_price(remaining_accounts, clock::Clock::get()?.unix_timestamp)?;
+
         // transfer stable token from initializer to vault
         self.transfer_to_vault(args.amount)?;
 
-        // $1USD amount equivalant to stable token amount
-        let ousd_amount = if self.stable_mint.decimals > self.ousd_mint.decimals {
-            args.amount
-                / u64::pow(
-                    10,
-                    (self.stable_mint.decimals - self.ousd_mint.decimals) as u32,
-                )
-        } else if self.stable_mint.decimals < self.ousd_mint.decimals {
-            args.amount
-                * u64::pow(
-                    10,
-                    (self.ousd_mint.decimals - self.stable_mint.decimals) as u32,
-                )
-        } else {
-            args.amount
-        };
+        // $1USD amount equivalant to stable token amount, rounded down
+        let ousd_amount = StableAmount(args.amount)
+            .to_ousd(self.stable_mint.decimals, self.ousd_mint.decimals)?
+            .0;
+
+        // mint fee, sent to treasury
+        let fee_amount = self.market.mint_fee(ousd_amount)?;
+        if ousd_amount - fee_amount < args.min_amount_out {
+            return Err(OneRingFinanceError::SlippageExceeded.into());
+        }
+        if fee_amount > 0 {
+            self.mint_to_treasury(fee_amount)?;
+        }
+
+        // track minted amount against market & global caps, and mint rate limit
+        self.market.add_outstanding(&mut self.state, ousd_amount)?;
+        self.market
+            .add_mint_volume(ousd_amount, clock::Clock::get()?.unix_timestamp)?;
 
         // mint deposit amount of 1USD to initializer
-        self.mint_to_initializer(ousd_amount)?;
+        self.mint_to_initializer(ousd_amount - fee_amount)?;
+
+        // reload 1USD mint to report the supply after minting
+        self.ousd_mint.reload()?;
+
+        emit!(MintOusdEvent {
+            initializer: self.initializer.key(),
+            market: self.market.key(),
+            stable_amount: args.amount,
+            ousd_amount,
+            fee_amount,
+            ousd_supply: self.ousd_mint.supply,
+        });
 
         Ok(())
     }
@@ -144,7 +228,7 @@ impl<'info> MintOusd<'info> {
 
 /// accounts for [redeem]
 #[derive(Accounts)]
-#[instruction(args: DepositOrWithdrawArgs)]
+#[instruction(args: RedeemArgs)]
 pub struct Redeem<'info> {
     /// user, redeem initializer
     pub initializer: Signer<'info>,
@@ -202,18 +286,37 @@ pub struct Redeem<'info> {
     )]
     pub initializer_ousd_token: Box<Account<'info, TokenAccount>>,
 
+    /// treasury 1USD token, receives fees
+    #[account(
+        mut,
+        constraint = treasury_ousd_token.key().eq(&state.treasury) @ OneRingFinanceError::InvalidTreasury,
+    )]
+    pub treasury_ousd_token: Box<Account<'info, TokenAccount>>,
+
     /// market state
+    /// queued redemptions are served first
     #[account(
-        // TODO: redeem logic TBD
-        // mut,
-        // constraint = market.withdrawal_liq >= args.amount @ OneRingFinanceError::InsufficientWithdrawalLiquidity,
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+        constraint = market.withdrawal_liq.saturating_sub(market.queued_amount) >= args.amount @ OneRingFinanceError::InsufficientWithdrawalLiquidity,
         constraint = !market.lock_flag @ OneRingFinanceError::MarketLocked,
     )]
     pub market: Box<Account<'info, Market>>,
 
     /// main state
     #[account(
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_REDEEM) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -243,6 +346,20 @@ impl<'info> Redeem<'info> {
         })
     }
 
+    /// transfer fee amount of 1USD from initializer to treasury
+    pub fn transfer_to_treasury(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = Transfer {
+            from: self.initializer_ousd_token.to_account_info(),
+            to: self.treasury_ousd_token.to_account_info(),
+            authority: self.initializer.to_account_info(),
+        };
+
+        token::transfer(
+            CpiContext::new(self.token_program.to_account_info(), cpi_accounts),
+            amount,
+        )
+    }
+
     /// burn redeem amount of 1USD from initializer
     pub fn burn_from_initializer(&self, amount: u64) -> ProgramResult {
         let cpi_accounts = Burn {
@@ -262,33 +379,53 @@ impl<'info> Redeem<'info> {
 impl<'info> Redeem<'info> {
     /// process [redeem]
     /// redeem, burn correspond amount of 1USD
-    pub fn process(&mut self, args: DepositOrWithdrawArgs) -> ProgramResult {
-        // stable amount equivalant to $1USD token amount
-        let stable_amount = if self.stable_mint.decimals > self.ousd_mint.decimals {
-            args.amount
-                * u64::pow(
-                    10,
-                    (self.stable_mint.decimals - self.ousd_mint.decimals) as u32,
-                )
-        } else if self.stable_mint.decimals < self.ousd_mint.decimals {
-            args.amount
-                / u64::pow(
-                    10,
-                    (self.ousd_mint.decimals - self.stable_mint.decimals) as u32,
-                )
-        } else {
-            args.amount
-        };
+    pub fn process(&mut self, args: RedeemArgs) -> ProgramResult {
+        check_expiry(args.expiry)?;
+
+        // track redeemed amount against redeem rate limit
+        self.market
+            .add_redeem_volume(args.amount, clock::Clock::get()?.unix_timestamp)?;
+
+        // redeem fee, sent to treasury
+        let fee_amount = self.market.redeem_fee(args.amount)?;
+        let ousd_amount = args.amount - fee_amount;
+
+        // stable amount equivalant to $1USD token amount, rounded down
+        let stable_amount = OusdAmount(ousd_amount)
+            .to_stable(self.stable_mint.decimals, self.ousd_mint.decimals)?
+            .0;
+
+        if stable_amount < args.min_amount_out {
+            return Err(OneRingFinanceError::SlippageExceeded.into());
+        }
 
         // transfer stable token from vault to initializer
         self.transfer_to_initializer(stable_amount)?;
 
+        // transfer fee amount of 1USD from initializer to treasury
+        if fee_amount > 0 {
+            self.transfer_to_treasury(fee_amount)?;
+        }
+
         // burn redeem amount of 1USD from initializer
-        self.burn_from_initializer(args.amount)?;
+        self.burn_from_initializer(ousd_amount)?;
 
-        // TODO: redeem logic TBD
         // reduct withdrawal liquid
-        // self.market.withdrawal_liq -= args.amount;
+        self.market.withdrawal_liq -= ousd_amount;
+        self.market.sub_outstanding(&mut self.state, ousd_amount);
+
+        // reload 1USD mint to report the supply after burning
+        self.ousd_mint.reload()?;
+
+        emit!(RedeemEvent {
+            initializer: self.initializer.key(),
+            market: self.market.key(),
+            ousd_amount: args.amount,
+            fee_amount,
+            stable_amount,
+            withdrawal_liq: self.market.withdrawal_liq,
+            ousd_supply: self.ousd_mint.supply,
+        });
 
         Ok(())
     }
@@ -1,11 +1,25 @@
 pub mod admin;
 pub mod deposit;
+pub mod flash;
 pub mod market;
+pub mod migrate;
 pub mod mint;
+pub mod redemption;
+pub mod reward;
+pub mod share;
+pub mod swap;
+pub mod timelock;
 pub mod yield_aggregator;
 
 pub use admin::*;
 pub use deposit::*;
+pub use flash::*;
 pub use market::*;
+pub use migrate::*;
 pub use mint::*;
+pub use redemption::*;
+pub use reward::*;
+pub use share::*;
+pub use swap::*;
+pub use timelock::*;
 pub use yield_aggregator::*;
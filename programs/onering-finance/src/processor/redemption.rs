@@ -0,0 +1,835 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use std::mem::size_of;
+
+use crate::{amount::*, args::*, constant::*, error::*, event::*, states::*, traits::*};
+
+//-----------------------------------------------------
+
+/// accounts for [queue_redemption]
+#[derive(Accounts)]
+#[instruction(args: QueueRedemptionArgs)]
+pub struct QueueRedemption<'info> {
+    /// user, redeem initializer
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        mut,
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD token
+    #[account(
+        mut,
+        constraint = initializer_ousd_token.owner.eq(initializer.key) @ OneRingFinanceError::InvalidOusdAccountOwner,
+        constraint = initializer_ousd_token.mint.eq(&ousd_mint.key()) @ OneRingFinanceError::InvalidOusdMint,
+        constraint = initializer_ousd_token.amount >= args.amount @ OneRingFinanceError::InsufficientOusdBalance,
+    )]
+    pub initializer_ousd_token: Box<Account<'info, TokenAccount>>,
+
+    /// redemption ticket, one per queue id so that a cancelled ticket never blocks queueing again
+    #[account(
+        init,
+        seeds = [
+            initializer.key().as_ref(),
+            REDEEM_TICKET_SEED.as_ref(),
+            market.key().as_ref(),
+            market.queue_tail.to_le_bytes().as_ref(),
+        ],
+        bump = args.nonce,
+        payer = initializer,
+        space = 8 + size_of::<RedeemTicket>(),
+    )]
+    pub ticket: Box<Account<'info, RedeemTicket>>,
+
+    /// market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+        constraint = !market.lock_flag @ OneRingFinanceError::MarketLocked,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_REDEEM) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+
+    /// system program
+    pub system_program: Program<'info, System>,
+}
+
+/// implementation for [QueueRedemption]
+impl<'info> QueueRedemption<'info> {
+    /// burn queued amount of 1USD from initializer
+    pub fn burn_from_initializer(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = Burn {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.initializer_ousd_token.to_account_info(),
+            authority: self.initializer.to_account_info(),
+        };
+
+        token::burn(
+            CpiContext::new(self.token_program.to_account_info(), cpi_accounts),
+            amount,
+        )
+    }
+
+    /// process [queue_redemption]
+    /// escrow 1USD into a ticket, waiting for withdrawal liquidity
+    pub fn process(&mut self, args: QueueRedemptionArgs) -> ProgramResult {
+        args.validate()?;
+
+        // only queued when unqueued withdrawal liquidity cannot serve an instant redeem
+        if self
+            .market
+            .withdrawal_liq
+            .saturating_sub(self.market.queued_amount)
+            >= args.amount
+        {
+            return Err(OneRingFinanceError::WithdrawalLiquiditySufficient.into());
+        }
+
+        self.ticket.nonce = args.nonce;
+        self.ticket.owner = self.initializer.key();
+        self.ticket.market = self.market.key();
+        self.ticket.queue_id = self.market.queue_tail;
+        self.ticket.ousd_amount = args.amount;
+        self.ticket.queue_time = clock::Clock::get()?.unix_timestamp;
+        self.ticket.fee_bps = self.market.redeem_fee_bps;
+        self.ticket.escrowed_amount = 0;
+
+        // push to the tail of queue
+        self.market.queue_tail = self
+            .market
+            .queue_tail
+            .checked_add(1)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+        self.market.queued_amount = self
+            .market
+            .queued_amount
+            .checked_add(args.amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+        self.state.queued_redeem_amount = self
+            .state
+            .queued_redeem_amount
+            .checked_add(args.amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        // burn queued amount of 1USD from initializer
+        self.burn_from_initializer(args.amount)?;
+
+        emit!(QueueRedemptionEvent {
+            initializer: self.initializer.key(),
+            market: self.market.key(),
+            ticket: self.ticket.key(),
+            queue_id: self.ticket.queue_id,
+            queue_position: self.ticket.queue_position(&self.market),
+            ousd_amount: args.amount,
+            fee_bps: self.ticket.fee_bps,
+            queued_amount: self.market.queued_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [fulfill_redemption]
+/// permissionless, anyone can crank the queue once liquidity arrives
+#[derive(Accounts)]
+pub struct FulfillRedemption<'info> {
+    /// fulfill initializer
+    pub initializer: Signer<'info>,
+
+    /// ticket owner, receives ticket rent
+    #[account(
+        mut,
+        constraint = owner.key().eq(&ticket.owner) @ OneRingFinanceError::AccessDenied,
+    )]
+    pub owner: UncheckedAccount<'info>,
+
+    /// stable mint
+    #[account(
+        constraint = stable_mint.key().eq(&market.stable_mint) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub stable_mint: Box<Account<'info, Mint>>,
+
+    /// stable vault
+    #[account(
+        mut,
+        seeds = [
+            market.stable_mint.key().as_ref(),
+            STABLE_VAULT_SEED.as_ref(),
+            market.key().as_ref()
+        ],
+        bump = market.stable_vault_bump,
+        constraint = stable_vault.mint.eq(&stable_mint.key()) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub stable_vault: Box<Account<'info, TokenAccount>>,
+
+    /// stable vault authority
+    #[account(
+        seeds = [
+            STABLE_VAULT_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.stable_vault_auth_bump,
+    )]
+    pub stable_vault_auth: UncheckedAccount<'info>,
+
+    /// owner stable token
+    #[account(
+        mut,
+        constraint = owner_stable_token.owner.eq(&ticket.owner) @ OneRingFinanceError::InvalidStableAccountOwner,
+        constraint = owner_stable_token.mint.eq(&stable_mint.key()) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub owner_stable_token: Box<Account<'info, TokenAccount>>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        mut,
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
+    pub ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// treasury 1USD token, receives fees
+    #[account(
+        mut,
+        constraint = treasury_ousd_token.key().eq(&state.treasury) @ OneRingFinanceError::InvalidTreasury,
+    )]
+    pub treasury_ousd_token: Box<Account<'info, TokenAccount>>,
+
+    /// redemption ticket
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            ticket.owner.as_ref(),
+            REDEEM_TICKET_SEED.as_ref(),
+            market.key().as_ref(),
+            ticket.queue_id.to_le_bytes().as_ref(),
+        ],
+        bump = ticket.nonce,
+        constraint = ticket.queue_id == market.queue_head @ OneRingFinanceError::NotQueueHead,
+    )]
+    pub ticket: Box<Account<'info, RedeemTicket>>,
+
+    /// market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+        constraint = market.withdrawal_liq >= ticket.ousd_amount @ OneRingFinanceError::InsufficientWithdrawalLiquidity,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_REDEEM) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [FulfillRedemption]
+impl<'info> FulfillRedemption<'info> {
+    /// transfer stable token from vault to ticket owner
+    pub fn transfer_to_owner(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = Transfer {
+            from: self.stable_vault.to_account_info(),
+            to: self.owner_stable_token.to_account_info(),
+            authority: self.stable_vault_auth.to_account_info(),
+        };
+
+        self.state.with_vault_auth_seeds(|auth_seeds| {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[auth_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
+    /// mint fee amount of 1USD to treasury
+    pub fn mint_to_treasury(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = MintTo {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.treasury_ousd_token.to_account_info(),
+            authority: self.ousd_mint_auth.to_account_info(),
+        };
+
+        self.state.with_mint_auth_seeds(|mint_seeds| {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[mint_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
+    /// process [fulfill_redemption]
+    /// pay out the ticket at the queue head, cancelled tickets are just popped
+    pub fn process(&mut self) -> ProgramResult {
+        let queued_amount = self.ticket.ousd_amount;
+        let mut fee_amount = 0;
+        let mut stable_amount = 0;
+
+        if queued_amount > 0 {
+            // settle against withdrawal liquidity, redeem fee is fixed at queue time
+            let (fee, ousd_amount) = self.market.settle_redemption(
+                &mut self.state,
+                &self.ticket,
+                clock::Clock::get()?.unix_timestamp,
+            )?;
+            fee_amount = fee;
+
+            // escrowed 1USD fee is minted to treasury
+            if fee_amount > 0 {
+                self.mint_to_treasury(fee_amount)?;
+            }
+
+            // stable amount equivalant to $1USD token amount, rounded down
+            stable_amount = OusdAmount(ousd_amount)
+                .to_stable(self.stable_mint.decimals, self.ousd_mint.decimals)?
+                .0;
+
+            // transfer stable token from vault to ticket owner
+            self.transfer_to_owner(stable_amount)?;
+        }
+
+        // pop from the head of queue, ticket is closed
+        self.market.queue_head += 1;
+
+        emit!(FulfillRedemptionEvent {
+            initializer: self.initializer.key(),
+            owner: self.ticket.owner,
+            market: self.market.key(),
+            queue_id: self.ticket.queue_id,
+            ousd_amount: queued_amount,
+            fee_amount,
+            stable_amount,
+            withdrawal_liq: self.market.withdrawal_liq,
+            queued_amount: self.market.queued_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [cancel_redemption]
+#[derive(Accounts)]
+pub struct CancelRedemption<'info> {
+    /// user, ticket owner
+    pub initializer: Signer<'info>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        mut,
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
+    pub ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// 1USD token
+    #[account(
+        mut,
+        constraint = initializer_ousd_token.owner.eq(initializer.key) @ OneRingFinanceError::InvalidOusdAccountOwner,
+        constraint = initializer_ousd_token.mint.eq(&ousd_mint.key()) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub initializer_ousd_token: Box<Account<'info, TokenAccount>>,
+
+    /// redemption ticket
+    #[account(
+        mut,
+        seeds = [
+            initializer.key().as_ref(),
+            REDEEM_TICKET_SEED.as_ref(),
+            market.key().as_ref(),
+            ticket.queue_id.to_le_bytes().as_ref(),
+        ],
+        bump = ticket.nonce,
+        constraint = ticket.ousd_amount > 0 @ OneRingFinanceError::TicketCancelled,
+    )]
+    pub ticket: Box<Account<'info, RedeemTicket>>,
+
+    /// market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_REDEEM) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [CancelRedemption]
+impl<'info> CancelRedemption<'info> {
+    /// mint escrowed amount of 1USD back to initializer
+    pub fn mint_to_initializer(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = MintTo {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.initializer_ousd_token.to_account_info(),
+            authority: self.ousd_mint_auth.to_account_info(),
+        };
+
+        self.state.with_mint_auth_seeds(|mint_seeds| {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[mint_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
+    /// process [cancel_redemption]
+    /// refund escrowed 1USD, ticket keeps its place and is closed when it reaches the queue head
+    pub fn process(&mut self) -> ProgramResult {
+        let ousd_amount = self.ticket.ousd_amount;
+
+        self.ticket.ousd_amount = 0;
+        self.market.queued_amount -= ousd_amount;
+        self.state.queued_redeem_amount -= ousd_amount;
+
+        // mint escrowed amount of 1USD back to initializer
+        self.mint_to_initializer(ousd_amount)?;
+
+        emit!(CancelRedemptionEvent {
+            initializer: self.initializer.key(),
+            market: self.market.key(),
+            queue_id: self.ticket.queue_id,
+            ousd_amount,
+            queued_amount: self.market.queued_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [escrow_redemption]
+/// unblocks the queue when the ticket owner cannot receive stable tokens,
+/// e.g. its stable token account is frozen or closed
+#[derive(Accounts)]
+pub struct EscrowRedemption<'info> {
+    /// admin or operator
+    #[account(
+        constraint = state.is_operator(initializer.key) @ OneRingFinanceError::AccessDenied,
+    )]
+    pub initializer: Signer<'info>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        mut,
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
+    pub ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// treasury 1USD token, receives fees
+    #[account(
+        mut,
+        constraint = treasury_ousd_token.key().eq(&state.treasury) @ OneRingFinanceError::InvalidTreasury,
+    )]
+    pub treasury_ousd_token: Box<Account<'info, TokenAccount>>,
+
+    /// redemption ticket, kept open until claimed
+    #[account(
+        mut,
+        seeds = [
+            ticket.owner.as_ref(),
+            REDEEM_TICKET_SEED.as_ref(),
+            market.key().as_ref(),
+            ticket.queue_id.to_le_bytes().as_ref(),
+        ],
+        bump = ticket.nonce,
+        constraint = ticket.queue_id == market.queue_head @ OneRingFinanceError::NotQueueHead,
+    )]
+    pub ticket: Box<Account<'info, RedeemTicket>>,
+
+    /// market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+        constraint = market.withdrawal_liq >= ticket.ousd_amount @ OneRingFinanceError::InsufficientWithdrawalLiquidity,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_REDEEM) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [EscrowRedemption]
+impl<'info> EscrowRedemption<'info> {
+    /// mint fee amount of 1USD to treasury
+    pub fn mint_to_treasury(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = MintTo {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.treasury_ousd_token.to_account_info(),
+            authority: self.ousd_mint_auth.to_account_info(),
+        };
+
+        self.state.with_mint_auth_seeds(|mint_seeds| {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[mint_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
+    /// process [escrow_redemption]
+    /// settle the ticket at the queue head like [fulfill_redemption],
+    /// but keep its stable tokens reserved in the vault until the owner claims them
+    pub fn process(&mut self) -> ProgramResult {
+        let queued_amount = self.ticket.ousd_amount;
+        let mut fee_amount = 0;
+        let mut escrowed_amount = 0;
+
+        if queued_amount > 0 {
+            // settle against withdrawal liquidity, redeem fee is fixed at queue time
+            let (fee, ousd_amount) = self.market.settle_redemption(
+                &mut self.state,
+                &self.ticket,
+                clock::Clock::get()?.unix_timestamp,
+            )?;
+            fee_amount = fee;
+            escrowed_amount = ousd_amount;
+
+            // escrowed 1USD fee is minted to treasury
+            if fee_amount > 0 {
+                self.mint_to_treasury(fee_amount)?;
+            }
+
+            // ticket can no longer be cancelled, only claimed
+            self.ticket.ousd_amount = 0;
+            self.ticket.escrowed_amount = escrowed_amount;
+            self.market.escrowed_amount = self
+                .market
+                .escrowed_amount
+                .checked_add(escrowed_amount)
+                .ok_or(OneRingFinanceError::MathOverflow)?;
+            self.state.escrowed_redeem_amount = self
+                .state
+                .escrowed_redeem_amount
+                .checked_add(escrowed_amount)
+                .ok_or(OneRingFinanceError::MathOverflow)?;
+        }
+
+        // pop from the head of queue, ticket stays open
+        self.market.queue_head += 1;
+
+        emit!(EscrowRedemptionEvent {
+            initializer: self.initializer.key(),
+            owner: self.ticket.owner,
+            market: self.market.key(),
+            queue_id: self.ticket.queue_id,
+            ousd_amount: queued_amount,
+            fee_amount,
+            escrowed_amount,
+            withdrawal_liq: self.market.withdrawal_liq,
+            queued_amount: self.market.queued_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [claim_redemption]
+#[derive(Accounts)]
+pub struct ClaimRedemption<'info> {
+    /// user, ticket owner, receives ticket rent
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+
+    /// stable mint
+    #[account(
+        constraint = stable_mint.key().eq(&market.stable_mint) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub stable_mint: Box<Account<'info, Mint>>,
+
+    /// stable vault
+    #[account(
+        mut,
+        seeds = [
+            market.stable_mint.key().as_ref(),
+            STABLE_VAULT_SEED.as_ref(),
+            market.key().as_ref()
+        ],
+        bump = market.stable_vault_bump,
+        constraint = stable_vault.mint.eq(&stable_mint.key()) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub stable_vault: Box<Account<'info, TokenAccount>>,
+
+    /// stable vault authority
+    #[account(
+        seeds = [
+            STABLE_VAULT_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.stable_vault_auth_bump,
+    )]
+    pub stable_vault_auth: UncheckedAccount<'info>,
+
+    /// recipient stable token, any account of the stable mint since the owner signs
+    #[account(
+        mut,
+        constraint = recipient_stable_token.mint.eq(&stable_mint.key()) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub recipient_stable_token: Box<Account<'info, TokenAccount>>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// redemption ticket
+    #[account(
+        mut,
+        close = initializer,
+        seeds = [
+            initializer.key().as_ref(),
+            REDEEM_TICKET_SEED.as_ref(),
+            market.key().as_ref(),
+            ticket.queue_id.to_le_bytes().as_ref(),
+        ],
+        bump = ticket.nonce,
+        constraint = ticket.queue_id < market.queue_head @ OneRingFinanceError::TicketNotEscrowed,
+    )]
+    pub ticket: Box<Account<'info, RedeemTicket>>,
+
+    /// market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_REDEEM) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [ClaimRedemption]
+impl<'info> ClaimRedemption<'info> {
+    /// transfer stable token from vault to recipient
+    pub fn transfer_to_recipient(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = Transfer {
+            from: self.stable_vault.to_account_info(),
+            to: self.recipient_stable_token.to_account_info(),
+            authority: self.stable_vault_auth.to_account_info(),
+        };
+
+        self.state.with_vault_auth_seeds(|auth_seeds| {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[auth_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
+    /// process [claim_redemption]
+    /// pay out an escrowed ticket, ticket is closed
+    pub fn process(&mut self) -> ProgramResult {
+        let ousd_amount = self.ticket.escrowed_amount;
+        let mut stable_amount = 0;
+
+        if ousd_amount > 0 {
+            // stable amount equivalant to $1USD token amount, rounded down
+            stable_amount = OusdAmount(ousd_amount)
+                .to_stable(self.stable_mint.decimals, self.ousd_mint.decimals)?
+                .0;
+
+            // transfer stable token from vault to recipient
+            self.transfer_to_recipient(stable_amount)?;
+
+            self.ticket.escrowed_amount = 0;
+            self.market.escrowed_amount -= ousd_amount;
+            self.state.escrowed_redeem_amount -= ousd_amount;
+        }
+
+        emit!(ClaimRedemptionEvent {
+            initializer: self.initializer.key(),
+            market: self.market.key(),
+            queue_id: self.ticket.queue_id,
+            ousd_amount,
+            stable_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [get_queue_position]
+#[derive(Accounts)]
+pub struct GetQueuePosition<'info> {
+    /// redemption ticket
+    #[account(
+        seeds = [
+            ticket.owner.as_ref(),
+            REDEEM_TICKET_SEED.as_ref(),
+            market.key().as_ref(),
+            ticket.queue_id.to_le_bytes().as_ref(),
+        ],
+        bump = ticket.nonce,
+    )]
+    pub ticket: Box<Account<'info, RedeemTicket>>,
+
+    /// market state
+    pub market: Box<Account<'info, Market>>,
+}
+
+/// implementation for [GetQueuePosition]
+impl<'info> GetQueuePosition<'info> {
+    /// process [get_queue_position]
+    /// read-only, the position is returned through the event
+    pub fn process(&self) -> ProgramResult {
+        emit!(QueuePositionEvent {
+            owner: self.ticket.owner,
+            market: self.market.key(),
+            ticket: self.ticket.key(),
+            queue_id: self.ticket.queue_id,
+            queue_position: self.ticket.queue_position(&self.market),
+            ousd_amount: self.ticket.ousd_amount,
+            queued_amount: self.market.queued_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
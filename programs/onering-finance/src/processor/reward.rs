@@ -0,0 +1,186 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::{amount::*, args::*, constant::*, error::*, event::*, states::*};
+
+//-----------------------------------------------------
+
+/// accounts for [distribute_rewards]
+/// remaining accounts are triples of (market, stable vault, stable mint) for every market,
+/// used to verify vault assets backing the distributed rewards
+#[derive(Accounts)]
+#[instruction(args: DistributeRewardsArgs)]
+pub struct DistributeRewards<'info> {
+    /// admin, harvested yield provider
+    pub admin: Signer<'info>,
+
+    /// stable mint of harvested yield
+    #[account(
+        constraint = stable_mint.key().eq(&market.stable_mint) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub stable_mint: Box<Account<'info, Mint>>,
+
+    /// stable vault of the market, receives harvested yield
+    #[account(
+        mut,
+        seeds = [
+            market.stable_mint.key().as_ref(),
+            STABLE_VAULT_SEED.as_ref(),
+            market.key().as_ref()
+        ],
+        bump = market.stable_vault_bump,
+        constraint = stable_vault.mint.eq(&stable_mint.key()) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub stable_vault: Box<Account<'info, TokenAccount>>,
+
+    /// market of harvested yield
+    #[account(
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market.stable_mint.as_ref(),
+        ],
+        bump = market.market_bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    /// stable vault authority
+    #[account(
+        seeds = [
+            STABLE_VAULT_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.stable_vault_auth_bump,
+    )]
+    pub stable_vault_auth: UncheckedAccount<'info>,
+
+    /// stable token, harvested yield sold into stable tokens
+    #[account(
+        mut,
+        constraint = admin_stable_token.owner.eq(admin.key) @ OneRingFinanceError::InvalidStableAccountOwner,
+        constraint = admin_stable_token.mint.eq(&stable_mint.key()) @ OneRingFinanceError::InvalidStableMint,
+        constraint = admin_stable_token.amount >= args.harvest_amount @ OneRingFinanceError::InsufficientStableBalance,
+    )]
+    pub admin_stable_token: Box<Account<'info, TokenAccount>>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = !state.is_paused(PAUSE_STRATEGY) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [DistributeRewards]
+impl<'info> DistributeRewards<'info> {
+    /// transfer harvested stable token from admin to vault
+    pub fn transfer_to_vault(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = Transfer {
+            from: self.admin_stable_token.to_account_info(),
+            to: self.stable_vault.to_account_info(),
+            authority: self.admin.to_account_info(),
+        };
+
+        token::transfer(
+            CpiContext::new(self.token_program.to_account_info(), cpi_accounts),
+            amount,
+        )
+    }
+
+    /// total vault assets in 1USD, every market vault is counted once
+    pub fn vault_assets(&self, remaining_accounts: &[AccountInfo]) -> Result<u128> {
+        if remaining_accounts.len() % 3 != 0 {
+            return Err(OneRingFinanceError::InvalidStableVault.into());
+        }
+
+        let state = self.state.key();
+        let mut market_keys: Vec<Pubkey> = vec![];
+        let mut assets: u128 = 0;
+
+        for triple in remaining_accounts.chunks(3) {
+            let market = Account::<Market>::try_from(&triple[0])?;
+            let stable_vault = Account::<TokenAccount>::try_from(&triple[1])?;
+            let stable_mint = Account::<Mint>::try_from(&triple[2])?;
+
+            // market PDA of main state and its stable vault PDA, counted once
+            if market.market_address(&state) != Some(market.key())
+                || market.stable_vault_address(&market.key()) != Some(stable_vault.key())
+                || market_keys.contains(&market.key())
+            {
+                return Err(OneRingFinanceError::InvalidStableVault.into());
+            }
+            if !stable_mint.key().eq(&market.stable_mint) {
+                return Err(OneRingFinanceError::InvalidStableMint.into());
+            }
+            market_keys.push(market.key());
+
+            let amount = StableAmount(stable_vault.amount)
+                .to_ousd(stable_mint.decimals, self.ousd_mint.decimals)?;
+
+            assets = assets
+                .checked_add(amount.0 as u128)
+                .ok_or(OneRingFinanceError::MathOverflow)?;
+        }
+
+        Ok(assets)
+    }
+
+    /// process [distribute_rewards]
+    /// credit rewards to main state, refused beyond the surplus of vault assets
+    pub fn process(
+        &mut self,
+        args: DistributeRewardsArgs,
+        remaining_accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        // transfer harvested stable token from admin to vault
+        if args.harvest_amount > 0 {
+            self.transfer_to_vault(args.harvest_amount)?;
+        }
+
+        // refused beyond the surplus of vault assets over outstanding 1USD
+        self.state.check_reward_surplus(
+            self.vault_assets(remaining_accounts)?,
+            self.ousd_mint.supply,
+            args.amount,
+        )?;
+
+        // accrue rewards to reserves
+        self.state.accrue_reward(args.amount)?;
+
+        // advance update time
+        let now = clock::Clock::get()?.unix_timestamp;
+        if self.state.first_update_time == 0 {
+            self.state.first_update_time = now;
+        }
+        self.state.last_update_time = now;
+
+        emit!(DistributeRewardsEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            harvest_amount: args.harvest_amount,
+            amount: args.amount,
+            reward_index: self.state.reward_index,
+            total_deposit_amount: self.state.deposit_amount,
+            total_reward_amount: self.state.reward_amount,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
@@ -0,0 +1,352 @@
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount};
+
+use crate::{args::*, constant::*, error::*, event::*, states::*, traits::*};
+
+//-----------------------------------------------------
+
+/// accounts for [enable_share_mode]
+#[derive(Accounts)]
+pub struct EnableShareMode<'info> {
+    /// admin
+    pub admin: Signer<'info>,
+
+    /// share token (s1USD) mint, minted by 1USD mint authority
+    #[account(
+        constraint = share_mint.mint_authority == COption::Some(ousd_mint_auth.key()) @ OneRingFinanceError::InvalidShareMint,
+        constraint = share_mint.freeze_authority.is_none() @ OneRingFinanceError::InvalidShareMint,
+        constraint = share_mint.supply == 0 @ OneRingFinanceError::InvalidShareMint,
+        constraint = share_mint.decimals == ousd_mint.decimals @ OneRingFinanceError::InvalidShareMint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
+    pub ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+        constraint = state.share_mint.eq(&Pubkey::default()) @ OneRingFinanceError::ShareModeEnabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [EnableShareMode]
+impl<'info> EnableShareMode<'info> {
+    /// process [enable_share_mode]
+    pub fn process(&mut self) -> ProgramResult {
+        self.state.share_mint = self.share_mint.key();
+        self.state.total_shares = 0;
+        self.state.total_underlying = 0;
+
+        emit!(EnableShareModeEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            share_mint: self.share_mint.key(),
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [deposit_shares]
+#[derive(Accounts)]
+#[instruction(args: DepositOrWithdrawArgs)]
+pub struct DepositShares<'info> {
+    /// user, deposit initializer
+    pub initializer: Signer<'info>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        mut,
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD token
+    #[account(
+        mut,
+        constraint = initializer_ousd_token.owner.eq(initializer.key) @ OneRingFinanceError::InvalidOusdAccountOwner,
+        constraint = initializer_ousd_token.mint.eq(&ousd_mint.key()) @ OneRingFinanceError::InvalidOusdMint,
+        constraint = initializer_ousd_token.amount >= args.amount @ OneRingFinanceError::InsufficientOusdBalance,
+    )]
+    pub initializer_ousd_token: Box<Account<'info, TokenAccount>>,
+
+    /// share token (s1USD) mint
+    #[account(
+        mut,
+        constraint = share_mint.key().eq(&state.share_mint) @ OneRingFinanceError::InvalidShareMint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// share token, receives minted shares
+    #[account(
+        mut,
+        constraint = initializer_share_token.mint.eq(&share_mint.key()) @ OneRingFinanceError::InvalidShareMint,
+    )]
+    pub initializer_share_token: Box<Account<'info, TokenAccount>>,
+
+    /// 1USD mint authority, share mint authority as well
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
+    pub ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.share_mint.eq(&Pubkey::default()) @ OneRingFinanceError::ShareModeDisabled,
+        constraint = !state.is_paused(PAUSE_DEPOSIT) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [DepositShares]
+impl<'info> DepositShares<'info> {
+    /// burn deposit amount of 1USD from initializer
+    pub fn burn_from_initializer(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = Burn {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.initializer_ousd_token.to_account_info(),
+            authority: self.initializer.to_account_info(),
+        };
+
+        token::burn(
+            CpiContext::new(self.token_program.to_account_info(), cpi_accounts),
+            amount,
+        )
+    }
+
+    /// mint share tokens to initializer
+    pub fn mint_shares_to_initializer(&self, shares: u64) -> ProgramResult {
+        let cpi_accounts = MintTo {
+            mint: self.share_mint.to_account_info(),
+            to: self.initializer_share_token.to_account_info(),
+            authority: self.ousd_mint_auth.to_account_info(),
+        };
+
+        self.state.with_mint_auth_seeds(|mint_seeds| {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[mint_seeds],
+                ),
+                shares,
+            )
+        })
+    }
+
+    /// process [deposit_shares]
+    /// deposit 1USD for share tokens at the current exchange rate
+    pub fn process(&mut self, args: DepositOrWithdrawArgs) -> ProgramResult {
+        // share token amount, rounded down against the user
+        let shares = self.state.shares_for(args.amount)?;
+        if shares == 0 {
+            return Err(OneRingFinanceError::ZeroShares.into());
+        }
+
+        self.state.total_shares = self
+            .state
+            .total_shares
+            .checked_add(shares)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+        self.state.total_underlying = self
+            .state
+            .total_underlying
+            .checked_add(args.amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        // burn deposit amount of 1USD from initializer
+        self.burn_from_initializer(args.amount)?;
+
+        // mint share tokens to initializer
+        self.mint_shares_to_initializer(shares)?;
+
+        emit!(DepositSharesEvent {
+            initializer: self.initializer.key(),
+            amount: args.amount,
+            shares,
+            total_shares: self.state.total_shares,
+            total_underlying: self.state.total_underlying,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [withdraw_shares]
+#[derive(Accounts)]
+#[instruction(args: DepositOrWithdrawArgs)]
+pub struct WithdrawShares<'info> {
+    /// user, withdraw initializer
+    pub initializer: Signer<'info>,
+
+    /// 1USD mint, collateral asset
+    #[account(
+        mut,
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// 1USD token, receives withdrawn 1USD
+    #[account(
+        mut,
+        constraint = initializer_ousd_token.owner.eq(initializer.key) @ OneRingFinanceError::InvalidOusdAccountOwner,
+        constraint = initializer_ousd_token.mint.eq(&ousd_mint.key()) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub initializer_ousd_token: Box<Account<'info, TokenAccount>>,
+
+    /// share token (s1USD) mint
+    #[account(
+        mut,
+        constraint = share_mint.key().eq(&state.share_mint) @ OneRingFinanceError::InvalidShareMint,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    /// share token, shares are burnt
+    #[account(
+        mut,
+        constraint = initializer_share_token.owner.eq(initializer.key) @ OneRingFinanceError::InvalidShareAccountOwner,
+        constraint = initializer_share_token.mint.eq(&share_mint.key()) @ OneRingFinanceError::InvalidShareMint,
+        constraint = initializer_share_token.amount >= args.amount @ OneRingFinanceError::WithdrawalAmountTooMuch,
+    )]
+    pub initializer_share_token: Box<Account<'info, TokenAccount>>,
+
+    /// 1USD mint authority
+    #[account(
+        seeds = [
+            OUSD_MINT_AUTH_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.ousd_mint_auth_bump,
+    )]
+    pub ousd_mint_auth: UncheckedAccount<'info>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.share_mint.eq(&Pubkey::default()) @ OneRingFinanceError::ShareModeDisabled,
+        constraint = !state.is_paused(PAUSE_WITHDRAW) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [WithdrawShares]
+impl<'info> WithdrawShares<'info> {
+    /// burn share tokens from initializer
+    pub fn burn_shares_from_initializer(&self, shares: u64) -> ProgramResult {
+        let cpi_accounts = Burn {
+            mint: self.share_mint.to_account_info(),
+            to: self.initializer_share_token.to_account_info(),
+            authority: self.initializer.to_account_info(),
+        };
+
+        token::burn(
+            CpiContext::new(self.token_program.to_account_info(), cpi_accounts),
+            shares,
+        )
+    }
+
+    /// mint withdrawn amount of 1USD to initializer
+    pub fn mint_to_initializer(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = MintTo {
+            mint: self.ousd_mint.to_account_info(),
+            to: self.initializer_ousd_token.to_account_info(),
+            authority: self.ousd_mint_auth.to_account_info(),
+        };
+
+        self.state.with_mint_auth_seeds(|mint_seeds| {
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[mint_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
+    /// process [withdraw_shares]
+    /// burn share tokens for 1USD at the current exchange rate, `amount` is in shares
+    pub fn process(&mut self, args: DepositOrWithdrawArgs) -> ProgramResult {
+        // 1USD amount, rounded down against the user
+        let amount = self.state.underlying_for(args.amount)?;
+        if amount == 0 {
+            return Err(OneRingFinanceError::ZeroAmount.into());
+        }
+
+        self.state.total_shares = self
+            .state
+            .total_shares
+            .checked_sub(args.amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+        self.state.total_underlying = self
+            .state
+            .total_underlying
+            .checked_sub(amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        // burn share tokens from initializer
+        self.burn_shares_from_initializer(args.amount)?;
+
+        // mint withdrawn amount of 1USD to initializer
+        self.mint_to_initializer(amount)?;
+
+        emit!(WithdrawSharesEvent {
+            initializer: self.initializer.key(),
+            amount,
+            shares: args.amount,
+            total_shares: self.state.total_shares,
+            total_underlying: self.state.total_underlying,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
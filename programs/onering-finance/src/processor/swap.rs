@@ -0,0 +1,240 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use crate::{
+    amount::*, args::*, constant::*, error::*, event::*, processor::check_expiry, states::*,
+    traits::*,
+};
+
+//-----------------------------------------------------
+
+/// accounts for [swap_stable]
+#[derive(Accounts)]
+#[instruction(args: SwapStableArgs)]
+pub struct SwapStable<'info> {
+    /// user, swap initializer
+    pub initializer: Signer<'info>,
+
+    /// input stable mint
+    #[account(
+        constraint = stable_mint_in.key().eq(&market_in.stable_mint) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub stable_mint_in: Box<Account<'info, Mint>>,
+
+    /// input stable vault, receives input stable token
+    #[account(
+        mut,
+        seeds = [
+            market_in.stable_mint.key().as_ref(),
+            STABLE_VAULT_SEED.as_ref(),
+            market_in.key().as_ref()
+        ],
+        bump = market_in.stable_vault_bump,
+        constraint = stable_vault_in.mint.eq(&stable_mint_in.key()) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub stable_vault_in: Box<Account<'info, TokenAccount>>,
+
+    /// input stable token
+    #[account(
+        mut,
+        constraint = initializer_stable_token_in.owner.eq(initializer.key) @ OneRingFinanceError::InvalidStableAccountOwner,
+        constraint = initializer_stable_token_in.mint.eq(&stable_mint_in.key()) @ OneRingFinanceError::InvalidStableMint,
+        constraint = initializer_stable_token_in.amount >= args.amount @ OneRingFinanceError::InsufficientStableBalance,
+    )]
+    pub initializer_stable_token_in: Box<Account<'info, TokenAccount>>,
+
+    /// input market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market_in.stable_mint.as_ref(),
+        ],
+        bump = market_in.market_bump,
+        constraint = !market_in.lock_flag @ OneRingFinanceError::MarketLocked,
+    )]
+    pub market_in: Box<Account<'info, Market>>,
+
+    /// output stable mint
+    #[account(
+        constraint = stable_mint_out.key().eq(&market_out.stable_mint) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub stable_mint_out: Box<Account<'info, Mint>>,
+
+    /// output stable vault, pays out output stable token
+    #[account(
+        mut,
+        seeds = [
+            market_out.stable_mint.key().as_ref(),
+            STABLE_VAULT_SEED.as_ref(),
+            market_out.key().as_ref()
+        ],
+        bump = market_out.stable_vault_bump,
+        constraint = stable_vault_out.mint.eq(&stable_mint_out.key()) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub stable_vault_out: Box<Account<'info, TokenAccount>>,
+
+    /// output stable token
+    #[account(
+        mut,
+        constraint = initializer_stable_token_out.owner.eq(initializer.key) @ OneRingFinanceError::InvalidStableAccountOwner,
+        constraint = initializer_stable_token_out.mint.eq(&stable_mint_out.key()) @ OneRingFinanceError::InvalidStableMint,
+    )]
+    pub initializer_stable_token_out: Box<Account<'info, TokenAccount>>,
+
+    /// output market state
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            MARKET_SEED.as_ref(),
+            market_out.stable_mint.as_ref(),
+        ],
+        bump = market_out.market_bump,
+        constraint = !market_out.lock_flag @ OneRingFinanceError::MarketLocked,
+        constraint = market_out.key().ne(&market_in.key()) @ OneRingFinanceError::SameStableSwap,
+    )]
+    pub market_out: Box<Account<'info, Market>>,
+
+    /// stable vault authority
+    #[account(
+        seeds = [
+            STABLE_VAULT_SEED.as_ref(),
+            state.key().as_ref()
+        ],
+        bump = state.stable_vault_auth_bump,
+    )]
+    pub stable_vault_auth: UncheckedAccount<'info>,
+
+    /// 1USD mint, for decimals only
+    #[account(
+        constraint = ousd_mint.key().eq(&state.ousd_mint) @ OneRingFinanceError::InvalidOusdMint,
+    )]
+    pub ousd_mint: Box<Account<'info, Mint>>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = !state.is_paused(PAUSE_SWAP) @ OneRingFinanceError::ServiceDisabled,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// token program
+    pub token_program: Program<'info, Token>,
+}
+
+/// implementation for [SwapStable]
+impl<'info> SwapStable<'info> {
+    /// transfer input stable token from initializer to input vault
+    pub fn transfer_to_vault(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = Transfer {
+            from: self.initializer_stable_token_in.to_account_info(),
+            to: self.stable_vault_in.to_account_info(),
+            authority: self.initializer.to_account_info(),
+        };
+
+        token::transfer(
+            CpiContext::new(self.token_program.to_account_info(), cpi_accounts),
+            amount,
+        )
+    }
+
+    /// transfer output stable token from output vault to initializer
+    pub fn transfer_to_initializer(&self, amount: u64) -> ProgramResult {
+        let cpi_accounts = Transfer {
+            from: self.stable_vault_out.to_account_info(),
+            to: self.initializer_stable_token_out.to_account_info(),
+            authority: self.stable_vault_auth.to_account_info(),
+        };
+
+        self.state.with_vault_auth_seeds(|auth_seeds| {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    &[auth_seeds],
+                ),
+                amount,
+            )
+        })
+    }
+
+    /// process [swap_stable]
+    /// swap stable tokens through the vaults, 1USD supply is untouched
+    pub fn process(
+        &mut self,
+        args: SwapStableArgs,
+        remaining_accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        check_expiry(args.expiry)?;
+
+        let now = clock::Clock::get()?.unix_timestamp;
+
+        // reject stale or depegged price of either stable
+        self.market_in.check_price(remaining_accounts, now)?;
+        self.market_out.check_price(remaining_accounts, now)?;
+
+        // $1USD amount equivalant to input stable token amount, rounded down
+        let ousd_amount = StableAmount(args.amount)
+            .to_ousd(self.stable_mint_in.decimals, self.ousd_mint.decimals)?
+            .0;
+
+        // swap fee, kept in the input vault as surplus
+        let fee_amount = self.market_out.swap_fee(ousd_amount)?;
+        let ousd_amount_out = ousd_amount - fee_amount;
+
+        // output stable amount equivalant to $1USD amount, rounded down
+        let stable_amount_out = OusdAmount(ousd_amount_out)
+            .to_stable(self.stable_mint_out.decimals, self.ousd_mint.decimals)?
+            .0;
+
+        if stable_amount_out < args.min_amount_out {
+            return Err(OneRingFinanceError::SlippageExceeded.into());
+        }
+
+        // queued redemptions are served first
+        if self
+            .market_out
+            .withdrawal_liq
+            .saturating_sub(self.market_out.queued_amount)
+            < ousd_amount_out
+        {
+            return Err(OneRingFinanceError::InsufficientWithdrawalLiquidity.into());
+        }
+
+        // track swapped amounts against rate limits and move backing between markets
+        self.market_in.add_mint_volume(ousd_amount, now)?;
+        self.market_out.add_redeem_volume(ousd_amount_out, now)?;
+        self.market_out
+            .move_outstanding(&mut self.market_in, ousd_amount_out)?;
+
+        // transfer input stable token from initializer to input vault
+        self.transfer_to_vault(args.amount)?;
+
+        // transfer output stable token from output vault to initializer
+        self.transfer_to_initializer(stable_amount_out)?;
+
+        // reduct withdrawal liquid
+        self.market_out.withdrawal_liq -= ousd_amount_out;
+
+        emit!(SwapStableEvent {
+            initializer: self.initializer.key(),
+            market_in: self.market_in.key(),
+            market_out: self.market_out.key(),
+            stable_amount_in: args.amount,
+            ousd_amount,
+            fee_amount,
+            stable_amount_out,
+            withdrawal_liq: self.market_out.withdrawal_liq,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
@@ -0,0 +1,269 @@
+use anchor_lang::{prelude::*, solana_program::clock};
+use anchor_spl::token::TokenAccount;
+use std::mem::size_of;
+
+use crate::{args::*, constant::*, error::*, event::*, states::*};
+
+//-----------------------------------------------------
+
+/// accounts for [queue_action]
+#[derive(Accounts)]
+#[instruction(args: QueueActionArgs)]
+pub struct QueueAction<'info> {
+    /// admin, pays for queued action rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// queued action
+    #[account(
+        init,
+        seeds = [
+            state.key().as_ref(),
+            QUEUED_ACTION_SEED.as_ref(),
+            state.action_count.to_le_bytes().as_ref(),
+        ],
+        bump = args.nonce,
+        payer = admin,
+        space = 8 + size_of::<QueuedAction>(),
+    )]
+    pub action: Box<Account<'info, QueuedAction>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+    )]
+    pub state: Box<Account<'info, State>>,
+
+    /// system program
+    pub system_program: Program<'info, System>,
+}
+
+/// implementation for [QueueAction]
+impl<'info> QueueAction<'info> {
+    /// process [queue_action]
+    /// record an admin action, executable after the timelock delay of main state
+    pub fn process(&mut self, args: QueueActionArgs) -> ProgramResult {
+        // reject invalid arguments early, they are checked again on execution
+        args.action.validate()?;
+
+        let eta = clock::Clock::get()?
+            .unix_timestamp
+            .checked_add(self.state.timelock_delay)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        self.action.nonce = args.nonce;
+        self.action.state = self.state.key();
+        self.action.action_id = self.state.action_count;
+        self.action.action = args.action;
+        self.action.eta = eta;
+
+        self.state.action_count += 1;
+
+        emit!(QueueActionEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            action_id: self.action.action_id,
+            action: args.action,
+            eta,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
+
+/// accounts for [execute_queued_action]
+/// target market & treasury accounts of the action are passed as remaining accounts
+#[derive(Accounts)]
+pub struct ExecuteQueuedAction<'info> {
+    /// admin, receives queued action rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// queued action, closed once executed
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            QUEUED_ACTION_SEED.as_ref(),
+            action.action_id.to_le_bytes().as_ref(),
+        ],
+        bump = action.nonce,
+        close = admin,
+    )]
+    pub action: Box<Account<'info, QueuedAction>>,
+
+    /// main state
+    #[account(
+        mut,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [ExecuteQueuedAction]
+impl<'info> ExecuteQueuedAction<'info> {
+    /// process [execute_queued_action]
+    pub fn process(&mut self, remaining_accounts: &[AccountInfo]) -> ProgramResult {
+        if clock::Clock::get()?.unix_timestamp < self.action.eta {
+            return Err(OneRingFinanceError::TimelockNotReady.into());
+        }
+
+        self.action.action.validate()?;
+
+        match self.action.action {
+            TimelockAction::SetTimelockDelay { delay } => {
+                self.state.timelock_delay = delay;
+            }
+            TimelockAction::ProposeAdmin { new_admin } => {
+                self.state.pending_admin = new_admin;
+            }
+            TimelockAction::UpdateRoles { args } => {
+                self.state.guardian = args.guardian;
+                self.state.operator = args.operator;
+                self.state.harvester = args.harvester;
+            }
+            TimelockAction::UpdateMarket { market, args } => {
+                let mut market = load_market(remaining_accounts, &self.state.key(), &market)?;
+                market.apply_update(&args)?;
+                market.exit(&crate::ID)?;
+            }
+            TimelockAction::UpdateFees { market, args } => {
+                let mut market = load_market(remaining_accounts, &self.state.key(), &market)?;
+                market.apply_fees(&args)?;
+                market.exit(&crate::ID)?;
+            }
+            TimelockAction::UpdateCaps { market, args } => {
+                let mut market = load_market(remaining_accounts, &self.state.key(), &market)?;
+                market.supply_cap = args.supply_cap;
+                market.exit(&crate::ID)?;
+
+                self.state.global_cap = args.global_cap;
+            }
+            TimelockAction::UpdateRateLimits { market, args } => {
+                let mut market = load_market(remaining_accounts, &self.state.key(), &market)?;
+                market.apply_rate_limits(&args)?;
+                market.exit(&crate::ID)?;
+            }
+            TimelockAction::UpdateOracle { market, args } => {
+                let mut market = load_market(remaining_accounts, &self.state.key(), &market)?;
+                market.apply_oracle(&args)?;
+                market.exit(&crate::ID)?;
+            }
+            TimelockAction::UpdateFlashMint { args } => {
+                self.state.apply_flash_mint(&args)?;
+            }
+            TimelockAction::UpdateWithdrawCooldown { args } => {
+                self.state.apply_withdraw_cooldown(&args)?;
+            }
+            TimelockAction::UpdateTreasury { treasury } => {
+                let treasury_info = remaining_accounts
+                    .iter()
+                    .find(|account| account.key.eq(&treasury))
+                    .ok_or(OneRingFinanceError::InvalidQueuedAction)?;
+                let treasury_token = Account::<TokenAccount>::try_from(treasury_info)?;
+                if treasury_token.mint != self.state.ousd_mint {
+                    return Err(OneRingFinanceError::InvalidOusdMint.into());
+                }
+
+                self.state.treasury = treasury;
+            }
+        }
+
+        emit!(ExecuteQueuedActionEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            action_id: self.action.action_id,
+            action: self.action.action,
+        });
+
+        Ok(())
+    }
+}
+
+/// find the market account of a queued action among remaining accounts
+fn load_market<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    state: &Pubkey,
+    market: &Pubkey,
+) -> Result<Account<'info, Market>> {
+    let market_info = remaining_accounts
+        .iter()
+        .find(|account| account.key.eq(market))
+        .ok_or(OneRingFinanceError::InvalidQueuedAction)?;
+
+    if !market_info.is_writable {
+        return Err(OneRingFinanceError::InvalidQueuedAction.into());
+    }
+
+    let market = Account::<Market>::try_from(market_info)?;
+
+    // market PDA of main state
+    if market.market_address(state) != Some(market.key()) {
+        return Err(OneRingFinanceError::InvalidQueuedAction.into());
+    }
+
+    Ok(market)
+}
+
+//-----------------------------------------------------
+
+/// accounts for [cancel_queued_action]
+#[derive(Accounts)]
+pub struct CancelQueuedAction<'info> {
+    /// admin, receives queued action rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// queued action, closed
+    #[account(
+        mut,
+        seeds = [
+            state.key().as_ref(),
+            QUEUED_ACTION_SEED.as_ref(),
+            action.action_id.to_le_bytes().as_ref(),
+        ],
+        bump = action.nonce,
+        close = admin,
+    )]
+    pub action: Box<Account<'info, QueuedAction>>,
+
+    /// main state
+    #[account(
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        has_one = admin @ OneRingFinanceError::AccessDenied,
+    )]
+    pub state: Box<Account<'info, State>>,
+}
+
+/// implementation for [CancelQueuedAction]
+impl<'info> CancelQueuedAction<'info> {
+    /// process [cancel_queued_action]
+    pub fn process(&mut self) -> ProgramResult {
+        emit!(CancelQueuedActionEvent {
+            admin: self.admin.key(),
+            state: self.state.key(),
+            action_id: self.action.action_id,
+        });
+
+        Ok(())
+    }
+}
+
+//-----------------------------------------------------
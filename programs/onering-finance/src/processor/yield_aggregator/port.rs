@@ -1,21 +1,26 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 
-use crate::{args::*, error::*, states::*, traits::*};
+use crate::{args::*, constant::*, error::*, event::*, states::*, traits::*};
 
 //-----------------------------------------------------
 
 /// accounts for [PortDeposit]
 #[derive(Accounts)]
 pub struct PortDeposit<'info> {
-    /// admin
+    /// admin or operator
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub operator: Signer<'info>,
 
     /// main state
     #[account(
-        has_one = admin @ OneRingFinanceError::AccessDenied,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.is_operator(operator.key) @ OneRingFinanceError::AccessDenied,
+        constraint = !state.is_paused(PAUSE_STRATEGY) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -66,7 +71,15 @@ impl<'info> PortDeposit<'info> {
                 cpi_context.with_signer(&[auth_seeds]),
                 args.amount,
             )
-        })
+        })?;
+
+        emit!(PortDepositEvent {
+            authority: self.operator.key(),
+            reserve: self.reserve.key(),
+            amount: args.amount,
+        });
+
+        Ok(())
     }
 }
 
@@ -75,14 +88,19 @@ impl<'info> PortDeposit<'info> {
 /// accounts for [PortDepositAndCollateralize]
 #[derive(Accounts)]
 pub struct PortDepositAndCollateralize<'info> {
-    /// admin
+    /// admin or operator
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub operator: Signer<'info>,
 
     /// main state
     #[account(
-        has_one = admin @ OneRingFinanceError::AccessDenied,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.is_operator(operator.key) @ OneRingFinanceError::AccessDenied,
+        constraint = !state.is_paused(PAUSE_STRATEGY) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -149,7 +167,15 @@ impl<'info> PortDepositAndCollateralize<'info> {
                 cpi_context.with_signer(&[auth_seeds]),
                 args.amount,
             )
-        })
+        })?;
+
+        emit!(PortDepositAndCollateralizeEvent {
+            authority: self.operator.key(),
+            reserve: self.reserve.key(),
+            amount: args.amount,
+        });
+
+        Ok(())
     }
 }
 
@@ -158,14 +184,19 @@ impl<'info> PortDepositAndCollateralize<'info> {
 /// accounts for [port_withdraw]
 #[derive(Accounts)]
 pub struct PortWithdraw<'info> {
-    /// admin
+    /// admin or operator
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub operator: Signer<'info>,
 
     /// main state
     #[account(
-        has_one = admin @ OneRingFinanceError::AccessDenied,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.is_operator(operator.key) @ OneRingFinanceError::AccessDenied,
+        constraint = !state.is_paused(PAUSE_STRATEGY) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -216,7 +247,15 @@ impl<'info> PortWithdraw<'info> {
         let cpi_context =
             CpiContext::new(self.port_finance_program.to_account_info(), cpi_accounts);
 
-        port_anchor_adaptor::withdraw(cpi_context, args.amount)
+        port_anchor_adaptor::withdraw(cpi_context, args.amount)?;
+
+        emit!(PortWithdrawEvent {
+            authority: self.operator.key(),
+            reserve: self.reserve.key(),
+            amount: args.amount,
+        });
+
+        Ok(())
     }
 }
 
@@ -224,14 +263,19 @@ impl<'info> PortWithdraw<'info> {
 
 #[derive(Accounts)]
 pub struct PortClaimReward<'info> {
-    /// admin
+    /// admin or harvester
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub harvester: Signer<'info>,
 
     /// main state
     #[account(
-        has_one = admin @ OneRingFinanceError::AccessDenied,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.is_harvester(harvester.key) @ OneRingFinanceError::AccessDenied,
+        constraint = !state.is_paused(PAUSE_STRATEGY) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -272,7 +316,15 @@ impl<'info> PortClaimReward<'info> {
         let cpi_context =
             CpiContext::new(self.port_finance_program.to_account_info(), cpi_accounts);
 
-        port_anchor_adaptor::claim_reward(cpi_context)
+        port_anchor_adaptor::claim_reward(cpi_context)?;
+
+        emit!(PortClaimRewardEvent {
+            authority: self.harvester.key(),
+            staking_pool: self.staking_pool.key(),
+            reward_dest: self.reward_dest.key(),
+        });
+
+        Ok(())
     }
 }
 
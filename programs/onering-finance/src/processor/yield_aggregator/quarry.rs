@@ -1,17 +1,22 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 
-use crate::{args::*, error::*, states::*, traits::*};
+use crate::{args::*, constant::*, error::*, event::*, states::*, traits::*};
 
 #[derive(Accounts)]
 pub struct QuarryUserStake<'info> {
-    /// admin
-    pub admin: Signer<'info>,
+    /// admin or operator
+    pub operator: Signer<'info>,
 
     /// main state
     #[account(
-        has_one = admin @ OneRingFinanceError::AccessDenied,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.is_operator(operator.key) @ OneRingFinanceError::AccessDenied,
+        constraint = !state.is_paused(PAUSE_STRATEGY) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -71,7 +76,16 @@ impl<'info> QuarryUserStake<'info> {
                 self.to_user_stake_cpi_context().with_signer(&[auth_seeds]),
                 args.amount,
             )
-        })
+        })?;
+
+        emit!(QuarryStakeTokensEvent {
+            authority: self.operator.key(),
+            quarry: self.quarry.key(),
+            miner: self.miner.key(),
+            amount: args.amount,
+        });
+
+        Ok(())
     }
 
     /// process [withdraw_tokens]
@@ -81,6 +95,15 @@ impl<'info> QuarryUserStake<'info> {
                 self.to_user_stake_cpi_context().with_signer(&[auth_seeds]),
                 args.amount,
             )
-        })
+        })?;
+
+        emit!(QuarryWithdrawTokensEvent {
+            authority: self.operator.key(),
+            quarry: self.quarry.key(),
+            miner: self.miner.key(),
+            amount: args.amount,
+        });
+
+        Ok(())
     }
 }
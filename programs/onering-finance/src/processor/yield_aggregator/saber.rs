@@ -1,7 +1,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::Token;
 
-use crate::{error::*, states::*, traits::*};
+use crate::{constant::*, error::*, event::*, states::*, traits::*};
 
 //-----------------------------------------------------
 
@@ -30,14 +30,19 @@ pub struct SaberWithdrawOneArgs {
 /// accounts for [saber_deposit]
 #[derive(Accounts)]
 pub struct SaberDeposit<'info> {
-    /// admin
+    /// admin or operator
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub operator: Signer<'info>,
 
     /// main state
     #[account(
-        has_one = admin @ OneRingFinanceError::AccessDenied,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.is_operator(operator.key) @ OneRingFinanceError::AccessDenied,
+        constraint = !state.is_paused(PAUSE_STRATEGY) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -109,7 +114,17 @@ impl<'info> SaberDeposit<'info> {
                 args.token_b_amount,
                 args.min_mint_amount,
             )
-        })
+        })?;
+
+        emit!(SaberDepositEvent {
+            authority: self.operator.key(),
+            swap: self.swap.key(),
+            token_a_amount: args.token_a_amount,
+            token_b_amount: args.token_b_amount,
+            min_mint_amount: args.min_mint_amount,
+        });
+
+        Ok(())
     }
 }
 
@@ -118,14 +133,19 @@ impl<'info> SaberDeposit<'info> {
 /// accounts for [saber_withdraw]
 #[derive(Accounts)]
 pub struct SaberWithdraw<'info> {
-    /// admin
+    /// admin or operator
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub operator: Signer<'info>,
 
     /// main state
     #[account(
-        has_one = admin @ OneRingFinanceError::AccessDenied,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.is_operator(operator.key) @ OneRingFinanceError::AccessDenied,
+        constraint = !state.is_paused(PAUSE_STRATEGY) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -211,7 +231,17 @@ impl<'info> SaberWithdraw<'info> {
                 args.minimum_token_a_amount,
                 args.minimum_token_b_amount,
             )
-        })
+        })?;
+
+        emit!(SaberWithdrawEvent {
+            authority: self.operator.key(),
+            swap: self.swap.key(),
+            pool_token_amount: args.pool_token_amount,
+            minimum_token_a_amount: args.minimum_token_a_amount,
+            minimum_token_b_amount: args.minimum_token_b_amount,
+        });
+
+        Ok(())
     }
 }
 
@@ -220,14 +250,19 @@ impl<'info> SaberWithdraw<'info> {
 /// accounts for [saber_withdraw_one]
 #[derive(Accounts)]
 pub struct SaberWithdrawOne<'info> {
-    /// admin
+    /// admin or operator
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub operator: Signer<'info>,
 
     /// main state
     #[account(
-        has_one = admin @ OneRingFinanceError::AccessDenied,
-        constraint = !state.emergency_flag @ OneRingFinanceError::ServiceDisabled,
+        seeds = [
+            STATE_SEED.as_ref(),
+            state.ousd_mint.as_ref(),
+        ],
+        bump = state.state_bump,
+        constraint = state.is_operator(operator.key) @ OneRingFinanceError::AccessDenied,
+        constraint = !state.is_paused(PAUSE_STRATEGY) @ OneRingFinanceError::ServiceDisabled,
     )]
     pub state: Box<Account<'info, State>>,
 
@@ -298,7 +333,16 @@ impl<'info> SaberWithdrawOne<'info> {
                 args.pool_token_amount,
                 args.minimum_token_amount,
             )
-        })
+        })?;
+
+        emit!(SaberWithdrawOneEvent {
+            authority: self.operator.key(),
+            swap: self.swap.key(),
+            pool_token_amount: args.pool_token_amount,
+            minimum_token_amount: args.minimum_token_amount,
+        });
+
+        Ok(())
     }
 }
 
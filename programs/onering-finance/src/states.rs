@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{constant::*, located::*, traits::*};
+use crate::{args::*, constant::*, error::*, located::*, oracle::*, traits::*};
 
 //-----------------------------------------------------
 
@@ -36,8 +36,242 @@ pub struct State {
     /// last total reward update time
     pub last_update_time: i64,
 
-    /// emergency flag
-    pub emergency_flag: bool,
+    /// pause flags, a set of `PAUSE_*` bits
+    /// takes the place of the legacy emergency flag, migrated to [PAUSE_ALL]
+    pub pause_flags: u8,
+
+    /// accumulated reward per weighted deposited 1USD, scaled by [REWARD_INDEX_SCALE]
+    /// increased whenever rewards are accrued, never decreased
+    pub reward_index: u128,
+
+    /// total 1USD amount waiting in redemption queues of all markets
+    pub queued_redeem_amount: u64,
+
+    /// treasury 1USD token account, receives mint, redeem & early exit fees
+    pub treasury: Pubkey,
+
+    /// proposed admin address, becomes admin once accepted
+    /// default pubkey if no admin transfer is pending
+    pub pending_admin: Pubkey,
+
+    /// guardian address, can only pause main state and lock markets
+    pub guardian: Pubkey,
+
+    /// operator address, can move funds between yield strategies
+    pub operator: Pubkey,
+
+    /// harvester address, can only claim rewards from yield strategies
+    pub harvester: Pubkey,
+
+    /// delay of timelocked admin actions in seconds, 0 if timelock is disabled
+    pub timelock_delay: i64,
+
+    /// id of the next queued action
+    pub action_count: u64,
+
+    /// bump seed for main state PDA
+    pub state_bump: u8,
+
+    /// upper bound of 1USD minted against all markets, 0 if uncapped
+    pub global_cap: u64,
+
+    /// 1USD minted against all markets and not yet redeemed
+    pub outstanding_amount: u64,
+
+    /// upper bound of 1USD flash minted at once, 0 if flash mint is disabled
+    pub flash_mint_cap: u64,
+
+    /// fee of flash mints, in basis points
+    pub flash_fee_bps: u16,
+
+    /// share token (s1USD) mint, default pubkey if share mode is disabled
+    pub share_mint: Pubkey,
+
+    /// total share token supply
+    pub total_shares: u64,
+
+    /// total 1USD amount backing share tokens, rewards included
+    pub total_underlying: u64,
+
+    /// total deposit amount weighted by reserve lock weights, used to distribute rewards
+    pub weighted_deposit_amount: u64,
+
+    /// delay between request_withdraw and complete_withdraw in seconds
+    pub withdraw_cooldown: i64,
+
+    /// fee of instant withdraw, in basis points
+    pub early_exit_fee_bps: u16,
+
+    /// total 1USD amount requested for withdrawal and waiting for the cooldown
+    pub pending_withdraw_amount: u64,
+
+    /// total reward amount settled into reserves and not claimed yet
+    /// accumulated when reserve states are refreshed, reducted when rewards are claimed
+    pub unclaimed_reward_amount: u64,
+
+    /// legacy keypair main state this one was migrated from, default pubkey if none
+    /// reserves derived from it are migrated to this main state
+    pub legacy_state: Pubkey,
+
+    /// total 1USD amount of redemptions escrowed out of all queues and not claimed yet
+    pub escrowed_redeem_amount: u64,
+}
+
+impl State {
+    /// whether any of the given pause flags is set
+    pub fn is_paused(&self, flags: u8) -> bool {
+        self.pause_flags & flags != 0
+    }
+
+    /// admin or guardian
+    pub fn is_guardian(&self, key: &Pubkey) -> bool {
+        self.admin.eq(key) || self.guardian.eq(key)
+    }
+
+    /// admin or operator
+    pub fn is_operator(&self, key: &Pubkey) -> bool {
+        self.admin.eq(key) || self.operator.eq(key)
+    }
+
+    /// share token amount for 1USD amount at the current exchange rate, rounded down
+    pub fn shares_for(&self, amount: u64) -> Result<u64> {
+        if self.total_shares == 0 || self.total_underlying == 0 {
+            return Ok(amount);
+        }
+
+        let shares = (amount as u128)
+            .checked_mul(self.total_shares as u128)
+            .ok_or(OneRingFinanceError::MathOverflow)?
+            / (self.total_underlying as u128);
+
+        Ok(u64::try_from(shares).map_err(|_| OneRingFinanceError::MathOverflow)?)
+    }
+
+    /// 1USD amount for share token amount at the current exchange rate, rounded down
+    pub fn underlying_for(&self, shares: u64) -> Result<u64> {
+        if self.total_shares == 0 {
+            return Ok(0);
+        }
+
+        let amount = (shares as u128)
+            .checked_mul(self.total_underlying as u128)
+            .ok_or(OneRingFinanceError::MathOverflow)?
+            / (self.total_shares as u128);
+
+        Ok(u64::try_from(amount).map_err(|_| OneRingFinanceError::MathOverflow)?)
+    }
+
+    /// update flash mint cap & fee
+    pub fn apply_flash_mint(&mut self, args: &UpdateFlashMintArgs) -> ProgramResult {
+        args.validate()?;
+
+        self.flash_mint_cap = args.flash_mint_cap;
+        self.flash_fee_bps = args.flash_fee_bps;
+
+        Ok(())
+    }
+
+    /// flash mint fee of 1USD amount, rounded up
+    pub fn flash_fee(&self, amount: u64) -> Result<u64> {
+        fee_amount(amount, self.flash_fee_bps)
+    }
+
+    /// update withdraw cooldown & early exit fee, pending withdrawals keep their cooldown
+    pub fn apply_withdraw_cooldown(&mut self, args: &UpdateWithdrawCooldownArgs) -> ProgramResult {
+        args.validate()?;
+
+        self.withdraw_cooldown = args.withdraw_cooldown;
+        self.early_exit_fee_bps = args.early_exit_fee_bps;
+
+        Ok(())
+    }
+
+    /// instant withdraw fee of 1USD amount, rounded up
+    pub fn early_exit_fee(&self, amount: u64) -> Result<u64> {
+        fee_amount(amount, self.early_exit_fee_bps)
+    }
+
+    /// admin or harvester
+    pub fn is_harvester(&self, key: &Pubkey) -> bool {
+        self.admin.eq(key) || self.harvester.eq(key)
+    }
+
+    /// outstanding 1USD; circulating, deposited, pending withdrawal, backing shares,
+    /// queued or escrowed for redemption, and rewards both distributed and settled but unclaimed
+    pub fn liabilities(&self, ousd_supply: u64) -> Result<u128> {
+        let liabilities = (ousd_supply as u128)
+            .checked_add(self.deposit_amount as u128)
+            .and_then(|v| v.checked_add(self.pending_withdraw_amount as u128))
+            .and_then(|v| v.checked_add(self.total_underlying as u128))
+            .and_then(|v| v.checked_add(self.queued_redeem_amount as u128))
+            .and_then(|v| v.checked_add(self.escrowed_redeem_amount as u128))
+            .and_then(|v| v.checked_add(self.reward_amount as u128))
+            .and_then(|v| v.checked_add(self.unclaimed_reward_amount as u128))
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        Ok(liabilities)
+    }
+
+    /// check that reward amount is covered by the surplus of vault assets over liabilities
+    pub fn check_reward_surplus(
+        &self,
+        vault_assets: u128,
+        ousd_supply: u64,
+        amount: u64,
+    ) -> ProgramResult {
+        let surplus = vault_assets.saturating_sub(self.liabilities(ousd_supply)?);
+        if (amount as u128) > surplus {
+            return Err(OneRingFinanceError::InsufficientSurplus.into());
+        }
+
+        Ok(())
+    }
+
+    /// accrue reward amount, distributed pro-rata between weighted reserve deposits and share tokens
+    /// reserve portion goes to the reward index, share portion compounds into the exchange rate
+    pub fn accrue_reward(&mut self, amount: u64) -> ProgramResult {
+        let total_amount = (self.weighted_deposit_amount as u128) + (self.total_underlying as u128);
+
+        // nobody to distribute to
+        if total_amount == 0 {
+            return Err(OneRingFinanceError::NoDepositLiquidity.into());
+        }
+
+        // share portion, rounded down in favor of reserves
+        let share_amount = (amount as u128)
+            .checked_mul(self.total_underlying as u128)
+            .ok_or(OneRingFinanceError::MathOverflow)?
+            / total_amount;
+        let share_amount = share_amount as u64;
+        self.total_underlying = self
+            .total_underlying
+            .checked_add(share_amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        let amount = amount - share_amount;
+        if amount == 0 {
+            return Ok(());
+        }
+
+        // reward per weighted deposited 1USD, rounded down
+        // dust left by rounding stays in `reward_amount` and is never claimable
+        let index_delta = (amount as u128)
+            .checked_mul(REWARD_INDEX_SCALE)
+            .ok_or(OneRingFinanceError::MathOverflow)?
+            / (self.weighted_deposit_amount as u128);
+
+        self.reward_index = self
+            .reward_index
+            .checked_add(index_delta)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        self.reward_amount = self
+            .reward_amount
+            .checked_add(amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        Ok(())
+    }
 }
 
 /// mint authority seeds
@@ -84,6 +318,414 @@ pub struct Market {
 
     /// lock flag
     pub lock_flag: bool,
+
+    /// queue id of the next redemption ticket to be fulfilled
+    pub queue_head: u64,
+
+    /// queue id of the next redemption ticket to be queued
+    pub queue_tail: u64,
+
+    /// total 1USD amount waiting in the redemption queue
+    pub queued_amount: u64,
+
+    /// mint fee in basis points
+    pub mint_fee_bps: u16,
+
+    /// redeem fee in basis points
+    pub redeem_fee_bps: u16,
+
+    /// bump seed for market PDA
+    pub market_bump: u8,
+
+    /// upper bound of 1USD minted against this market, 0 if uncapped
+    pub supply_cap: u64,
+
+    /// 1USD minted against this market and not yet redeemed
+    pub outstanding_amount: u64,
+
+    /// rate limit window in seconds, 0 if rate limits are disabled
+    pub rate_limit_window: i64,
+
+    /// maximum 1USD mint volume per window, 0 if unlimited
+    pub mint_limit: u64,
+
+    /// maximum 1USD redeem volume per window, 0 if unlimited
+    pub redeem_limit: u64,
+
+    /// rolling 1USD mint volume, decays by `mint_limit` per window
+    pub mint_volume: u64,
+
+    /// rolling 1USD redeem volume, decays by `redeem_limit` per window
+    pub redeem_volume: u64,
+
+    /// last rolling volume update time
+    pub volume_update_time: i64,
+
+    /// stable price feed account
+    pub oracle: Pubkey,
+
+    /// stable price feed layout, none if mint is not guarded
+    pub oracle_source: u8,
+
+    /// minimum stable price to mint, in basis points of $1
+    pub min_price_bps: u16,
+
+    /// maximum stable price age in seconds
+    pub max_price_age: i64,
+
+    /// fee of stable swaps paid out of this market, in basis points
+    pub swap_fee_bps: u16,
+
+    /// 1USD amount of redemptions escrowed out of the queue, waiting to be claimed
+    pub escrowed_amount: u64,
+}
+
+impl Market {
+    /// market PDA of main state, derived with the stored bump
+    pub fn market_address(&self, state: &Pubkey) -> Option<Pubkey> {
+        Pubkey::create_program_address(
+            &[
+                state.as_ref(),
+                MARKET_SEED,
+                self.stable_mint.as_ref(),
+                &[self.market_bump],
+            ],
+            &crate::ID,
+        )
+        .ok()
+    }
+
+    /// stable vault PDA of this market, derived with the stored bump
+    pub fn stable_vault_address(&self, market: &Pubkey) -> Option<Pubkey> {
+        Pubkey::create_program_address(
+            &[
+                self.stable_mint.as_ref(),
+                STABLE_VAULT_SEED,
+                market.as_ref(),
+                &[self.stable_vault_bump],
+            ],
+            &crate::ID,
+        )
+        .ok()
+    }
+
+    /// lock or unlock market, set or increment withdrawal liquidity
+    pub fn apply_update(&mut self, args: &UpdateMarketArgs) -> ProgramResult {
+        self.lock_flag = args.lock_flag;
+
+        self.withdrawal_liq = if args.increment_withdrawal_liq {
+            self.withdrawal_liq
+                .checked_add(args.withdrawal_liq)
+                .ok_or(OneRingFinanceError::MathOverflow)?
+        } else {
+            args.withdrawal_liq
+        };
+
+        Ok(())
+    }
+
+    /// track 1USD minted against this market, enforcing market & global caps
+    pub fn add_outstanding(&mut self, state: &mut State, amount: u64) -> ProgramResult {
+        let outstanding_amount = self
+            .outstanding_amount
+            .checked_add(amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+        let total_outstanding_amount = state
+            .outstanding_amount
+            .checked_add(amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        if (self.supply_cap > 0 && outstanding_amount > self.supply_cap)
+            || (state.global_cap > 0 && total_outstanding_amount > state.global_cap)
+        {
+            return Err(OneRingFinanceError::CapExceeded.into());
+        }
+
+        self.outstanding_amount = outstanding_amount;
+        state.outstanding_amount = total_outstanding_amount;
+
+        Ok(())
+    }
+
+    /// release 1USD redeemed from this market,
+    /// saturates since 1USD minted before tracking started is not accounted
+    pub fn sub_outstanding(&mut self, state: &mut State, amount: u64) {
+        self.outstanding_amount = self.outstanding_amount.saturating_sub(amount);
+        state.outstanding_amount = state.outstanding_amount.saturating_sub(amount);
+    }
+
+    /// update rate limit window and limits, rolling volumes are reset
+    pub fn apply_rate_limits(&mut self, args: &UpdateRateLimitsArgs) -> ProgramResult {
+        args.validate()?;
+
+        self.rate_limit_window = args.window;
+        self.mint_limit = args.mint_limit;
+        self.redeem_limit = args.redeem_limit;
+        self.mint_volume = 0;
+        self.redeem_volume = 0;
+
+        Ok(())
+    }
+
+    /// add 1USD amount to the rolling mint volume, enforcing the mint limit
+    pub fn add_mint_volume(&mut self, amount: u64, now: i64) -> ProgramResult {
+        self.decay_volumes(now);
+
+        if self.rate_limit_window > 0 && self.mint_limit > 0 {
+            let mint_volume = self
+                .mint_volume
+                .checked_add(amount)
+                .ok_or(OneRingFinanceError::MathOverflow)?;
+            if mint_volume > self.mint_limit {
+                return Err(OneRingFinanceError::MintRateLimitExceeded.into());
+            }
+
+            self.mint_volume = mint_volume;
+        }
+
+        Ok(())
+    }
+
+    /// add 1USD amount to the rolling redeem volume, enforcing the redeem limit
+    pub fn add_redeem_volume(&mut self, amount: u64, now: i64) -> ProgramResult {
+        self.decay_volumes(now);
+
+        if self.rate_limit_window > 0 && self.redeem_limit > 0 {
+            let redeem_volume = self
+                .redeem_volume
+                .checked_add(amount)
+                .ok_or(OneRingFinanceError::MathOverflow)?;
+            if redeem_volume > self.redeem_limit {
+                return Err(OneRingFinanceError::RedeemRateLimitExceeded.into());
+            }
+
+            self.redeem_volume = redeem_volume;
+        }
+
+        Ok(())
+    }
+
+    /// release rolling volumes in proportion to the time elapsed since the last update
+    fn decay_volumes(&mut self, now: i64) {
+        if self.rate_limit_window > 0 {
+            let elapsed = now.saturating_sub(self.volume_update_time).max(0);
+            self.mint_volume = decay_volume(
+                self.mint_volume,
+                self.mint_limit,
+                elapsed,
+                self.rate_limit_window,
+            );
+            self.redeem_volume = decay_volume(
+                self.redeem_volume,
+                self.redeem_limit,
+                elapsed,
+                self.rate_limit_window,
+            );
+        }
+
+        self.volume_update_time = now;
+    }
+
+    /// update stable price feed and depeg guard parameters
+    pub fn apply_oracle(&mut self, args: &UpdateOracleArgs) -> ProgramResult {
+        args.validate()?;
+
+        if args.oracle_source == ORACLE_SOURCE_NONE {
+            self.oracle = Pubkey::default();
+            self.oracle_source = ORACLE_SOURCE_NONE;
+            self.min_price_bps = 0;
+            self.max_price_age = 0;
+
+            return Ok(());
+        }
+
+        self.oracle = args.oracle;
+        self.oracle_source = args.oracle_source;
+        self.min_price_bps = args.min_price_bps;
+        self.max_price_age = args.max_price_age;
+
+        Ok(())
+    }
+
+    /// reject mint if stable price is stale or below the threshold,
+    /// price feed account is looked up among `accounts`
+    pub fn check_price(&self, accounts: &[AccountInfo], now: i64) -> ProgramResult {
+        if self.oracle_source == ORACLE_SOURCE_NONE {
+            return Ok(());
+        }
+
+        let oracle_info = accounts
+            .iter()
+            .find(|account| account.key.eq(&self.oracle))
+            .ok_or(OneRingFinanceError::InvalidOracle)?;
+        let price = load_price(self.oracle_source, &oracle_info.try_borrow_data()?)?;
+
+        if now.saturating_sub(price.timestamp) > self.max_price_age {
+            return Err(OneRingFinanceError::StalePrice.into());
+        }
+        if price.price_bps < self.min_price_bps as u64 {
+            return Err(OneRingFinanceError::StablePriceTooLow.into());
+        }
+
+        Ok(())
+    }
+
+    /// update mint & redeem fees
+    pub fn apply_fees(&mut self, args: &UpdateFeesArgs) -> ProgramResult {
+        args.validate()?;
+
+        self.mint_fee_bps = args.mint_fee_bps;
+        self.redeem_fee_bps = args.redeem_fee_bps;
+        self.swap_fee_bps = args.swap_fee_bps;
+
+        Ok(())
+    }
+
+    /// mint fee of 1USD amount, rounded up
+    pub fn mint_fee(&self, amount: u64) -> Result<u64> {
+        fee_amount(amount, self.mint_fee_bps)
+    }
+
+    /// redeem fee of 1USD amount, rounded up
+    pub fn redeem_fee(&self, amount: u64) -> Result<u64> {
+        fee_amount(amount, self.redeem_fee_bps)
+    }
+
+    /// swap fee of 1USD amount paid out of this market, rounded up
+    pub fn swap_fee(&self, amount: u64) -> Result<u64> {
+        fee_amount(amount, self.swap_fee_bps)
+    }
+
+    /// settle the ticket at the queue head against withdrawal liquidity,
+    /// charging the fee fixed at queue time, returns fee and net 1USD amounts
+    pub fn settle_redemption(
+        &mut self,
+        state: &mut State,
+        ticket: &RedeemTicket,
+        now: i64,
+    ) -> Result<(u64, u64)> {
+        let queued_amount = ticket.ousd_amount;
+
+        // track redeemed amount against redeem rate limit
+        self.add_redeem_volume(queued_amount, now)?;
+
+        let fee_amount = ticket.redeem_fee()?;
+        let ousd_amount = queued_amount - fee_amount;
+
+        // reduct withdrawal liquid
+        self.withdrawal_liq = self
+            .withdrawal_liq
+            .checked_sub(ousd_amount)
+            .ok_or(OneRingFinanceError::InsufficientWithdrawalLiquidity)?;
+        self.sub_outstanding(state, ousd_amount);
+        self.queued_amount -= queued_amount;
+        state.queued_redeem_amount -= queued_amount;
+
+        Ok((fee_amount, ousd_amount))
+    }
+
+    /// move 1USD backing of swapped stables from this market to `to`,
+    /// enforcing the market cap of `to`, global outstanding is unchanged
+    pub fn move_outstanding(&mut self, to: &mut Market, amount: u64) -> ProgramResult {
+        let outstanding_amount = to
+            .outstanding_amount
+            .checked_add(amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        if to.supply_cap > 0 && outstanding_amount > to.supply_cap {
+            return Err(OneRingFinanceError::CapExceeded.into());
+        }
+
+        to.outstanding_amount = outstanding_amount;
+        self.outstanding_amount = self.outstanding_amount.saturating_sub(amount);
+
+        Ok(())
+    }
+}
+
+/// volume left after `elapsed` seconds, `limit` is released per `window`
+fn decay_volume(volume: u64, limit: u64, elapsed: i64, window: i64) -> u64 {
+    let released = (limit as u128) * (elapsed as u128) / (window as u128);
+    if released >= volume as u128 {
+        0
+    } else {
+        volume - released as u64
+    }
+}
+
+/// fee portion of amount in basis points, rounded up
+fn fee_amount(amount: u64, fee_bps: u16) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_bps as u128)
+        .and_then(|v| v.checked_add(FEE_BPS_DENOMINATOR as u128 - 1))
+        .ok_or(OneRingFinanceError::MathOverflow)?
+        / FEE_BPS_DENOMINATOR as u128;
+
+    // fee never exceeds amount as long as fee_bps <= FEE_BPS_DENOMINATOR
+    Ok(fee as u64)
+}
+
+//-----------------------------------------------------
+
+/// redemption ticket, 1USD waiting for withdrawal liquidity
+#[account]
+pub struct RedeemTicket {
+    /// bump seed for ticket PDA
+    pub nonce: u8,
+
+    /// ticket owner, receives stable tokens
+    pub owner: Pubkey,
+
+    /// market to redeem from
+    pub market: Pubkey,
+
+    /// queue id, tickets are fulfilled in ascending order
+    pub queue_id: u64,
+
+    /// queued 1USD amount, burnt when queued, 0 if cancelled
+    pub ousd_amount: u64,
+
+    /// queued time, unix timestamp
+    pub queue_time: i64,
+
+    /// redeem fee of the market when queued, in basis points
+    pub fee_bps: u16,
+
+    /// 1USD amount net of fee escrowed out of the queue, paid in stable tokens when claimed
+    pub escrowed_amount: u64,
+}
+
+impl RedeemTicket {
+    /// redeem fee of the queued 1USD amount, rounded up
+    pub fn redeem_fee(&self) -> Result<u64> {
+        fee_amount(self.ousd_amount, self.fee_bps)
+    }
+
+    /// number of tickets ahead in the redemption queue
+    pub fn queue_position(&self, market: &Market) -> u64 {
+        self.queue_id.saturating_sub(market.queue_head)
+    }
+}
+
+//-----------------------------------------------------
+
+/// timelocked admin action, applied once `eta` has passed
+#[account]
+pub struct QueuedAction {
+    /// bump seed for queued action PDA
+    pub nonce: u8,
+
+    /// main state
+    pub state: Pubkey,
+
+    /// action id, taken from [State::action_count]
+    pub action_id: u64,
+
+    /// action with its arguments
+    pub action: TimelockAction,
+
+    /// earliest execution time, unix timestamp
+    pub eta: i64,
 }
 
 //-----------------------------------------------------
@@ -105,37 +747,1180 @@ pub struct Reserve {
 
     /// freeze flag, disable reserved account in case of emergency
     pub freeze_flag: bool,
+
+    /// checkpoint of [State::reward_index] at the last refresh
+    pub reward_index: u128,
+
+    /// unlock time of deposited principal, unix timestamp, 0 if never locked
+    pub unlock_time: i64,
+
+    /// reward weight of deposited principal, scaled by [LOCK_WEIGHT_BASE]
+    pub lock_weight: u16,
+
+    /// 1USD amount requested for withdrawal, earns no rewards
+    pub pending_amount: u64,
+
+    /// earliest completion time of the pending withdrawal, unix timestamp
+    pub withdrawable_time: i64,
+
+    /// delegate address, can only compound rewards by claim_and_deposit
+    /// default pubkey if no delegate is set
+    pub delegate: Pubkey,
 }
 
 impl Reserve {
     /// refresh reserve state
-    pub fn refresh_reserve(&mut self, state: &mut State) {
-        let elapsed_time =
-            if state.last_update_time > self.last_update_time && self.last_update_time > 0 {
-                state.last_update_time.saturating_sub(self.last_update_time)
-            } else {
-                0
-            };
-
+    /// settle rewards accrued since the last refresh into `reward_amount`, by weighted deposit amount
+    pub fn refresh_reserve(&mut self, state: &mut State) -> ProgramResult {
         // no rewards if current `deposit_amount` is 0
         if self.deposit_amount > 0 {
-            // proportional amount of deposit
-            let deposit_prop = self.deposit_amount / state.deposit_amount;
-            // proportional duration
-            let duration_prop = elapsed_time / (state.last_update_time - state.first_update_time);
-            // proportional reward
-            let reward_prop = state.reward_amount * deposit_prop * ((duration_prop as u32) as u64);
+            // reward per weighted deposited 1USD since the last refresh
+            let index_delta = state
+                .reward_index
+                .checked_sub(self.reward_index)
+                .ok_or(OneRingFinanceError::MathOverflow)?;
+
+            // proportional reward, rounded down
+            let reward_prop = (self.weighted_amount()? as u128)
+                .checked_mul(index_delta)
+                .ok_or(OneRingFinanceError::MathOverflow)?
+                / REWARD_INDEX_SCALE;
+            let reward_prop =
+                u64::try_from(reward_prop).map_err(|_| OneRingFinanceError::MathOverflow)?;
 
             // update reward amount
-            self.reward_amount += reward_prop;
+            self.reward_amount = self
+                .reward_amount
+                .checked_add(reward_prop)
+                .ok_or(OneRingFinanceError::MathOverflow)?;
 
             // update total reward amount
-            state.reward_amount -= reward_prop;
+            state.reward_amount = state
+                .reward_amount
+                .checked_sub(reward_prop)
+                .ok_or(OneRingFinanceError::MathOverflow)?;
+            state.unclaimed_reward_amount = state
+                .unclaimed_reward_amount
+                .checked_add(reward_prop)
+                .ok_or(OneRingFinanceError::MathOverflow)?;
         }
 
-        // update last updated time
+        // update checkpoint and last updated time
+        self.reward_index = state.reward_index;
         self.last_update_time = state.last_update_time;
+
+        Ok(())
+    }
+
+    /// reduct settled reward amount, when claimed or compounded
+    pub fn claim_reward(&mut self, state: &mut State, amount: u64) -> ProgramResult {
+        // check if claim amount less than reward amount
+        if self.reward_amount < amount {
+            return Err(OneRingFinanceError::ClaimAmountTooMuch.into());
+        }
+
+        self.reward_amount -= amount;
+        state.unclaimed_reward_amount = state
+            .unclaimed_reward_amount
+            .checked_sub(amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// deposit amount weighted by the lock weight, rounded down
+    pub fn weighted_amount(&self) -> Result<u64> {
+        let weighted_amount =
+            (self.deposit_amount as u128) * (self.lock_weight as u128) / (LOCK_WEIGHT_BASE as u128);
+
+        Ok(u64::try_from(weighted_amount).map_err(|_| OneRingFinanceError::MathOverflow)?)
+    }
+
+    /// initialize a newly created reserve, checkpointed at the current reward index
+    pub fn initialize(&mut self, nonce: u8, state: &State) {
+        self.nonce = nonce;
+        self.deposit_amount = 0;
+        self.reward_amount = 0;
+        self.last_update_time = 0;
+        self.freeze_flag = false;
+        self.reward_index = state.reward_index;
+        self.unlock_time = 0;
+        self.lock_weight = LOCK_WEIGHT_BASE;
+        self.pending_amount = 0;
+        self.withdrawable_time = 0;
+        self.delegate = Pubkey::default();
+    }
+
+    /// whether the reserve is initialized, lock weight is never 0 once created or migrated
+    pub fn is_initialized(&self) -> bool {
+        self.lock_weight != 0
+    }
+
+    /// move deposit & unclaimed rewards to another reserve, pending withdrawal stays
+    /// a locked deposit carries its lock over, and only into a reserve without deposit
+    /// later deposits of the recipient join the lock, so the recipient must consent to it
+    /// must be called after both reserves are refreshed
+    pub fn transfer_to(&mut self, to: &mut Reserve, state: &mut State, now: i64) -> ProgramResult {
+        // expired locks fall back to 1x weight
+        self.apply_lock(state, LOCK_TIER_NONE, now)?;
+        to.apply_lock(state, LOCK_TIER_NONE, now)?;
+
+        let deposit_amount = self.deposit_amount;
+        if self.is_locked(now) {
+            if to.deposit_amount > 0 {
+                return Err(OneRingFinanceError::ReserveNotEmpty.into());
+            }
+
+            // the lock ending last is kept, no weighted amount without deposit
+            if self.unlock_time >= to.unlock_time {
+                to.unlock_time = self.unlock_time;
+                to.lock_weight = self.lock_weight;
+            }
+        }
+
+        self.sub_deposit(state, deposit_amount)?;
+        to.add_deposit(state, deposit_amount)?;
+
+        // nothing left to lock
+        self.unlock_time = 0;
+        self.lock_weight = LOCK_WEIGHT_BASE;
+
+        to.reward_amount = to
+            .reward_amount
+            .checked_add(self.reward_amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+        self.reward_amount = 0;
+
+        Ok(())
+    }
+
+    /// whether the given address is the delegate of this reserve
+    pub fn is_delegate(&self, key: &Pubkey) -> bool {
+        !self.delegate.eq(&Pubkey::default()) && self.delegate.eq(key)
+    }
+
+    /// whether nothing is deposited, unclaimed or pending withdrawal
+    pub fn is_empty(&self) -> bool {
+        self.deposit_amount == 0 && self.reward_amount == 0 && self.pending_amount == 0
+    }
+
+    /// whether deposited principal is locked at the given time
+    pub fn is_locked(&self, now: i64) -> bool {
+        now < self.unlock_time
+    }
+
+    /// lock deposited principal for the given tier, an existing lock is never shortened
+    /// the weight follows the lock ending last, and falls back to 1x once the lock has expired
+    /// must be called after [Reserve::refresh_reserve], so that past rewards are settled at the old weight
+    pub fn apply_lock(&mut self, state: &mut State, lock_tier: u8, now: i64) -> ProgramResult {
+        let (lock_duration, lock_weight) = *LOCK_TIERS
+            .get(lock_tier as usize)
+            .ok_or(OneRingFinanceError::InvalidLockTier)?;
+
+        self.update_weighted(state, |reserve| {
+            if !reserve.is_locked(now) {
+                reserve.lock_weight = LOCK_WEIGHT_BASE;
+            }
+
+            if lock_tier != LOCK_TIER_NONE {
+                let unlock_time = now
+                    .checked_add(lock_duration)
+                    .ok_or(OneRingFinanceError::MathOverflow)?;
+                if unlock_time >= reserve.unlock_time {
+                    reserve.unlock_time = unlock_time;
+                    reserve.lock_weight = lock_weight;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// add to deposit amount, keeping total deposit amounts of main state in sync
+    pub fn add_deposit(&mut self, state: &mut State, amount: u64) -> ProgramResult {
+        self.update_weighted(state, |reserve| {
+            reserve.deposit_amount = reserve
+                .deposit_amount
+                .checked_add(amount)
+                .ok_or(OneRingFinanceError::MathOverflow)?;
+            Ok(())
+        })?;
+
+        state.deposit_amount = state
+            .deposit_amount
+            .checked_add(amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// reduct deposit amount, keeping total deposit amounts of main state in sync
+    pub fn sub_deposit(&mut self, state: &mut State, amount: u64) -> ProgramResult {
+        self.update_weighted(state, |reserve| {
+            reserve.deposit_amount = reserve
+                .deposit_amount
+                .checked_sub(amount)
+                .ok_or(OneRingFinanceError::WithdrawalAmountTooMuch)?;
+            Ok(())
+        })?;
+
+        state.deposit_amount = state
+            .deposit_amount
+            .checked_sub(amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// move amount from deposit into the pending bucket, restarting the cooldown
+    /// must be called after [Reserve::refresh_reserve], so that past rewards are settled
+    pub fn request_withdraw(&mut self, state: &mut State, amount: u64, now: i64) -> ProgramResult {
+        self.sub_deposit(state, amount)?;
+
+        self.pending_amount = self
+            .pending_amount
+            .checked_add(amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+        self.withdrawable_time = now
+            .checked_add(state.withdraw_cooldown)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        state.pending_withdraw_amount = state
+            .pending_withdraw_amount
+            .checked_add(amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// empty the pending bucket once the cooldown is over, returns the withdrawn amount
+    pub fn complete_withdraw(&mut self, state: &mut State, now: i64) -> Result<u64> {
+        if self.pending_amount == 0 {
+            return Err(OneRingFinanceError::NoPendingWithdraw.into());
+        }
+        if now < self.withdrawable_time {
+            return Err(OneRingFinanceError::WithdrawCooldownNotOver.into());
+        }
+
+        let amount = self.pending_amount;
+        self.pending_amount = 0;
+
+        state.pending_withdraw_amount = state
+            .pending_withdraw_amount
+            .checked_sub(amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        Ok(amount)
+    }
+
+    /// apply a change to deposit amount or lock weight, replacing the weighted amount in main state
+    fn update_weighted<F>(&mut self, state: &mut State, f: F) -> ProgramResult
+    where
+        F: FnOnce(&mut Reserve) -> ProgramResult,
+    {
+        let old_weighted_amount = self.weighted_amount()?;
+        f(self)?;
+        let new_weighted_amount = self.weighted_amount()?;
+
+        state.weighted_deposit_amount = state
+            .weighted_deposit_amount
+            .checked_sub(old_weighted_amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?
+            .checked_add(new_weighted_amount)
+            .ok_or(OneRingFinanceError::MathOverflow)?;
+
+        Ok(())
     }
 }
 
 //-----------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_state() -> State {
+        State {
+            admin: Pubkey::default(),
+            ousd_mint: Pubkey::default(),
+            ousd_mint_auth_bump: 0,
+            stable_vault_auth_bump: 0,
+            deposit_amount: 0,
+            reward_amount: 0,
+            first_update_time: 0,
+            last_update_time: 0,
+            pause_flags: 0,
+            reward_index: 0,
+            queued_redeem_amount: 0,
+            treasury: Pubkey::default(),
+            pending_admin: Pubkey::default(),
+            guardian: Pubkey::default(),
+            operator: Pubkey::default(),
+            harvester: Pubkey::default(),
+            timelock_delay: 0,
+            action_count: 0,
+            state_bump: 0,
+            global_cap: 0,
+            outstanding_amount: 0,
+            flash_mint_cap: 0,
+            flash_fee_bps: 0,
+            share_mint: Pubkey::default(),
+            total_shares: 0,
+            total_underlying: 0,
+            weighted_deposit_amount: 0,
+            withdraw_cooldown: 0,
+            early_exit_fee_bps: 0,
+            pending_withdraw_amount: 0,
+            unclaimed_reward_amount: 0,
+            legacy_state: Pubkey::default(),
+            escrowed_redeem_amount: 0,
+        }
+    }
+
+    fn new_market() -> Market {
+        Market {
+            stable_mint: Pubkey::default(),
+            stable_vault_bump: 0,
+            withdrawal_liq: 0,
+            lock_flag: false,
+            queue_head: 0,
+            queue_tail: 0,
+            queued_amount: 0,
+            mint_fee_bps: 0,
+            redeem_fee_bps: 0,
+            market_bump: 0,
+            supply_cap: 0,
+            outstanding_amount: 0,
+            rate_limit_window: 0,
+            mint_limit: 0,
+            redeem_limit: 0,
+            mint_volume: 0,
+            redeem_volume: 0,
+            volume_update_time: 0,
+            oracle: Pubkey::default(),
+            oracle_source: ORACLE_SOURCE_NONE,
+            min_price_bps: 0,
+            max_price_age: 0,
+            swap_fee_bps: 0,
+            escrowed_amount: 0,
+        }
+    }
+
+    fn new_reserve(state: &State) -> Reserve {
+        Reserve {
+            nonce: 0,
+            deposit_amount: 0,
+            reward_amount: 0,
+            last_update_time: 0,
+            freeze_flag: false,
+            reward_index: state.reward_index,
+            unlock_time: 0,
+            lock_weight: LOCK_WEIGHT_BASE,
+            pending_amount: 0,
+            withdrawable_time: 0,
+            delegate: Pubkey::default(),
+        }
+    }
+
+    fn deposit(reserve: &mut Reserve, state: &mut State, amount: u64) {
+        reserve.refresh_reserve(state).unwrap();
+        reserve.add_deposit(state, amount).unwrap();
+    }
+
+    fn withdraw(reserve: &mut Reserve, state: &mut State, amount: u64) {
+        reserve.refresh_reserve(state).unwrap();
+        reserve.sub_deposit(state, amount).unwrap();
+    }
+
+    #[test]
+    fn test_accrue_reward_without_deposit() {
+        let mut state = new_state();
+        assert!(state.accrue_reward(1_000).is_err());
+        assert_eq!(state.reward_index, 0);
+        assert_eq!(state.reward_amount, 0);
+    }
+
+    #[test]
+    fn test_pro_rata_reward() {
+        let mut state = new_state();
+        let mut alice = new_reserve(&state);
+        let mut bob = new_reserve(&state);
+
+        deposit(&mut alice, &mut state, 1_000_000_000);
+        deposit(&mut bob, &mut state, 3_000_000_000);
+        state.accrue_reward(4_000).unwrap();
+
+        alice.refresh_reserve(&mut state).unwrap();
+        bob.refresh_reserve(&mut state).unwrap();
+        assert_eq!(alice.reward_amount, 1_000);
+        assert_eq!(bob.reward_amount, 3_000);
+        assert_eq!(state.reward_amount, 0);
+
+        // refreshing twice does not pay twice
+        alice.refresh_reserve(&mut state).unwrap();
+        assert_eq!(alice.reward_amount, 1_000);
+    }
+
+    #[test]
+    fn test_small_depositor_receives_reward() {
+        let mut state = new_state();
+        let mut whale = new_reserve(&state);
+        let mut minnow = new_reserve(&state);
+
+        deposit(&mut whale, &mut state, 1_000_000_000_000_000);
+        deposit(&mut minnow, &mut state, 1_000_000);
+        state.accrue_reward(1_000_000_000_000).unwrap();
+
+        minnow.refresh_reserve(&mut state).unwrap();
+        assert_eq!(minnow.reward_amount, 999);
+    }
+
+    #[test]
+    fn test_enter_and_leave() {
+        let mut state = new_state();
+        let mut alice = new_reserve(&state);
+        let mut bob = new_reserve(&state);
+
+        deposit(&mut alice, &mut state, 100);
+        state.accrue_reward(1_000).unwrap();
+
+        // bob does not earn rewards accrued before his deposit
+        deposit(&mut bob, &mut state, 100);
+        state.accrue_reward(1_000).unwrap();
+
+        // alice does not earn rewards accrued after her withdrawal
+        withdraw(&mut alice, &mut state, 100);
+        state.accrue_reward(1_000).unwrap();
+
+        alice.refresh_reserve(&mut state).unwrap();
+        bob.refresh_reserve(&mut state).unwrap();
+        assert_eq!(alice.reward_amount, 1_500);
+        assert_eq!(bob.reward_amount, 1_500);
+        assert_eq!(state.reward_amount, 0);
+    }
+
+    #[test]
+    fn test_many_depositors() {
+        const USERS: usize = 32;
+        const ROUNDS: usize = 500;
+
+        let mut state = new_state();
+        let mut reserves: Vec<Reserve> = (0..USERS).map(|_| new_reserve(&state)).collect();
+        // expected rewards, scaled by 1e6 to keep precision
+        let mut expected = vec![0u128; USERS];
+        let mut accrued = 0u64;
+        let mut accruals = 0u128;
+
+        // deterministic pseudo random sequence
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        for _ in 0..ROUNDS {
+            let user = (next() % USERS as u64) as usize;
+            match next() % 3 {
+                0 => deposit(&mut reserves[user], &mut state, next() % 1_000_000_000_000),
+                1 => {
+                    let amount = next() % (reserves[user].deposit_amount + 1);
+                    withdraw(&mut reserves[user], &mut state, amount);
+                }
+                _ => {
+                    if state.deposit_amount == 0 {
+                        continue;
+                    }
+                    let reward = next() % 1_000_000_000;
+                    for (expected, reserve) in expected.iter_mut().zip(reserves.iter()) {
+                        *expected += reward as u128 * reserve.deposit_amount as u128 * 1_000_000
+                            / state.deposit_amount as u128;
+                    }
+                    state.accrue_reward(reward).unwrap();
+                    accrued += reward;
+                    accruals += 1;
+                }
+            }
+        }
+
+        let mut settled = 0u64;
+        for (expected, reserve) in expected.iter().zip(reserves.iter_mut()) {
+            reserve.refresh_reserve(&mut state).unwrap();
+            settled += reserve.reward_amount;
+
+            // rounded down at most once per accrual and once per refresh
+            let expected = (expected / 1_000_000) as u64;
+            assert!(reserve.reward_amount <= expected + 1);
+            assert!(expected.saturating_sub(reserve.reward_amount) <= (accruals + 1) as u64);
+        }
+
+        // rewards are never over distributed, dust stays in main state
+        assert!(settled <= accrued);
+        assert_eq!(state.reward_amount, accrued - settled);
+    }
+
+    #[test]
+    fn test_settled_rewards_are_liabilities() {
+        let mut state = new_state();
+        let mut reserve = new_reserve(&state);
+        deposit(&mut reserve, &mut state, 1_000_000_000);
+
+        // vault holds the deposit plus 1_000 harvested
+        let vault_assets = 1_000_001_000u128;
+        state.check_reward_surplus(vault_assets, 0, 1_000).unwrap();
+        state.accrue_reward(1_000).unwrap();
+
+        // settled rewards are still owed, not distributable again
+        reserve.refresh_reserve(&mut state).unwrap();
+        assert_eq!(state.reward_amount, 0);
+        assert_eq!(state.unclaimed_reward_amount, 1_000);
+        assert_eq!(
+            state.check_reward_surplus(vault_assets, 0, 1_000),
+            Err(OneRingFinanceError::InsufficientSurplus.into())
+        );
+
+        // claimed rewards are circulating 1USD
+        reserve.claim_reward(&mut state, 400).unwrap();
+        assert_eq!(state.unclaimed_reward_amount, 600);
+        assert_eq!(state.liabilities(400).unwrap(), vault_assets);
+        assert_eq!(
+            reserve.claim_reward(&mut state, 601),
+            Err(OneRingFinanceError::ClaimAmountTooMuch.into())
+        );
+    }
+
+    #[test]
+    fn test_weighted_reward() {
+        let mut state = new_state();
+        let mut alice = new_reserve(&state);
+        let mut bob = new_reserve(&state);
+
+        // alice locks for 12 months at 2x, bob does not lock
+        alice.apply_lock(&mut state, 3, 0).unwrap();
+        deposit(&mut alice, &mut state, 1_000_000_000);
+        deposit(&mut bob, &mut state, 1_000_000_000);
+        assert_eq!(state.deposit_amount, 2_000_000_000);
+        assert_eq!(state.weighted_deposit_amount, 3_000_000_000);
+
+        state.accrue_reward(3_000).unwrap();
+        alice.refresh_reserve(&mut state).unwrap();
+        bob.refresh_reserve(&mut state).unwrap();
+        assert_eq!(alice.reward_amount, 2_000);
+        assert_eq!(bob.reward_amount, 1_000);
+        assert_eq!(state.reward_amount, 0);
+
+        // expired lock falls back to 1x, rewards before are settled at 2x
+        state.accrue_reward(3_000).unwrap();
+        alice.refresh_reserve(&mut state).unwrap();
+        alice
+            .apply_lock(&mut state, LOCK_TIER_NONE, LOCK_TIERS[3].0)
+            .unwrap();
+        assert_eq!(alice.reward_amount, 4_000);
+        assert_eq!(state.weighted_deposit_amount, 2_000_000_000);
+
+        state.accrue_reward(2_000).unwrap();
+        alice.refresh_reserve(&mut state).unwrap();
+        bob.refresh_reserve(&mut state).unwrap();
+        assert_eq!(alice.reward_amount, 5_000);
+        assert_eq!(bob.reward_amount, 3_000);
+    }
+
+    #[test]
+    fn test_lock_tiers() {
+        let mut state = new_state();
+        let mut reserve = new_reserve(&state);
+        deposit(&mut reserve, &mut state, 1_000);
+
+        // unknown tier
+        assert!(reserve
+            .apply_lock(&mut state, LOCK_TIERS.len() as u8, 0)
+            .is_err());
+
+        // no lock
+        reserve.apply_lock(&mut state, LOCK_TIER_NONE, 0).unwrap();
+        assert!(!reserve.is_locked(0));
+        assert_eq!(reserve.lock_weight, LOCK_WEIGHT_BASE);
+
+        // 3 months
+        reserve.apply_lock(&mut state, 2, 100).unwrap();
+        assert_eq!(reserve.unlock_time, 100 + LOCK_TIERS[2].0);
+        assert_eq!(reserve.lock_weight, LOCK_TIERS[2].1);
+        assert!(reserve.is_locked(100 + LOCK_TIERS[2].0 - 1));
+        assert!(!reserve.is_locked(100 + LOCK_TIERS[2].0));
+        assert_eq!(state.weighted_deposit_amount, 1_500);
+
+        // shorter lock never shortens an existing lock
+        reserve.apply_lock(&mut state, 1, 200).unwrap();
+        assert_eq!(reserve.unlock_time, 100 + LOCK_TIERS[2].0);
+        assert_eq!(reserve.lock_weight, LOCK_TIERS[2].1);
+
+        // deposits without a tier join the existing lock
+        reserve.apply_lock(&mut state, LOCK_TIER_NONE, 300).unwrap();
+        reserve.add_deposit(&mut state, 1_000).unwrap();
+        assert_eq!(reserve.lock_weight, LOCK_TIERS[2].1);
+        assert_eq!(state.weighted_deposit_amount, 3_000);
+
+        // shorter lock ending later takes its own weight
+        let now = 100 + LOCK_TIERS[2].0 - 1;
+        reserve.apply_lock(&mut state, 1, now).unwrap();
+        assert_eq!(reserve.unlock_time, now + LOCK_TIERS[1].0);
+        assert_eq!(reserve.lock_weight, LOCK_TIERS[1].1);
+        assert_eq!(state.weighted_deposit_amount, 2_500);
+
+        // weighted total stays in sync on withdrawal
+        reserve.sub_deposit(&mut state, 2_000).unwrap();
+        assert_eq!(state.deposit_amount, 0);
+        assert_eq!(state.weighted_deposit_amount, 0);
+        assert!(reserve.sub_deposit(&mut state, 1).is_err());
+    }
+
+    #[test]
+    fn test_withdraw_cooldown_config() {
+        let mut state = new_state();
+
+        // negative or too long cooldown
+        for withdraw_cooldown in [-1, MAX_WITHDRAW_COOLDOWN + 1] {
+            assert!(state
+                .apply_withdraw_cooldown(&UpdateWithdrawCooldownArgs {
+                    withdraw_cooldown,
+                    early_exit_fee_bps: 0,
+                })
+                .is_err());
+        }
+
+        // fee too high
+        assert!(state
+            .apply_withdraw_cooldown(&UpdateWithdrawCooldownArgs {
+                withdraw_cooldown: 0,
+                early_exit_fee_bps: MAX_FEE_BPS + 1,
+            })
+            .is_err());
+
+        state
+            .apply_withdraw_cooldown(&UpdateWithdrawCooldownArgs {
+                withdraw_cooldown: 7 * 24 * 60 * 60,
+                early_exit_fee_bps: 50,
+            })
+            .unwrap();
+        assert_eq!(state.withdraw_cooldown, 7 * 24 * 60 * 60);
+
+        // rounded up
+        assert_eq!(state.early_exit_fee(1_000_000).unwrap(), 5_000);
+        assert_eq!(state.early_exit_fee(1).unwrap(), 1);
+        assert_eq!(state.early_exit_fee(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_pending_withdraw_stops_earning() {
+        let mut state = new_state();
+        state.withdraw_cooldown = 100;
+        let mut alice = new_reserve(&state);
+        let mut bob = new_reserve(&state);
+
+        deposit(&mut alice, &mut state, 1_000);
+        deposit(&mut bob, &mut state, 1_000);
+
+        // nothing pending yet
+        assert!(alice.complete_withdraw(&mut state, 0).is_err());
+
+        alice.refresh_reserve(&mut state).unwrap();
+        alice.request_withdraw(&mut state, 600, 10).unwrap();
+        assert_eq!(alice.withdrawable_time, 110);
+        assert_eq!(state.deposit_amount, 1_400);
+        assert_eq!(state.pending_withdraw_amount, 600);
+
+        // further request restarts the cooldown
+        alice.request_withdraw(&mut state, 400, 50).unwrap();
+        assert_eq!(alice.pending_amount, 1_000);
+        assert_eq!(alice.withdrawable_time, 150);
+
+        // pending amount earns nothing
+        state.accrue_reward(1_000).unwrap();
+        alice.refresh_reserve(&mut state).unwrap();
+        bob.refresh_reserve(&mut state).unwrap();
+        assert_eq!(alice.reward_amount, 0);
+        assert_eq!(bob.reward_amount, 1_000);
+
+        assert!(alice.complete_withdraw(&mut state, 149).is_err());
+        assert_eq!(alice.complete_withdraw(&mut state, 150).unwrap(), 1_000);
+        assert_eq!(alice.pending_amount, 0);
+        assert_eq!(state.pending_withdraw_amount, 0);
+        assert!(alice.complete_withdraw(&mut state, 150).is_err());
+    }
+
+    #[test]
+    fn test_reserve_is_empty() {
+        let mut state = new_state();
+        let mut reserve = new_reserve(&state);
+        assert!(reserve.is_empty());
+
+        deposit(&mut reserve, &mut state, 1_000);
+        assert!(!reserve.is_empty());
+
+        // unclaimed reward
+        state.accrue_reward(10).unwrap();
+        withdraw(&mut reserve, &mut state, 1_000);
+        assert_eq!(reserve.reward_amount, 10);
+        assert!(!reserve.is_empty());
+        reserve.reward_amount = 0;
+
+        // pending withdrawal
+        deposit(&mut reserve, &mut state, 1_000);
+        reserve.request_withdraw(&mut state, 1_000, 0).unwrap();
+        assert!(!reserve.is_empty());
+        reserve.complete_withdraw(&mut state, 0).unwrap();
+        assert!(reserve.is_empty());
+    }
+
+    #[test]
+    fn test_reserve_delegate() {
+        let state = new_state();
+        let mut reserve = new_reserve(&state);
+        let keeper = Pubkey::new_unique();
+
+        // no delegate, default pubkey never matches
+        assert!(!reserve.is_delegate(&keeper));
+        assert!(!reserve.is_delegate(&Pubkey::default()));
+
+        reserve.delegate = keeper;
+        assert!(reserve.is_delegate(&keeper));
+        assert!(!reserve.is_delegate(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_reserve_initialize() {
+        let mut state = new_state();
+        state.reward_index = 42;
+
+        // freshly allocated reserve is all zero
+        let mut reserve = Reserve::try_deserialize_unchecked(&mut &[0u8; 256][..]).unwrap();
+        assert!(!reserve.is_initialized());
+
+        reserve.initialize(7, &state);
+        assert!(reserve.is_initialized());
+        assert_eq!(reserve.nonce, 7);
+        assert_eq!(reserve.reward_index, 42);
+        assert_eq!(reserve.lock_weight, LOCK_WEIGHT_BASE);
+    }
+
+    #[test]
+    fn test_transfer_reserve() {
+        let mut state = new_state();
+        let mut alice = new_reserve(&state);
+        let mut bob = new_reserve(&state);
+        let mut carol = new_reserve(&state);
+
+        // alice locks for 12 months at 2x, bob & carol do not lock
+        alice.apply_lock(&mut state, 3, 0).unwrap();
+        deposit(&mut alice, &mut state, 1_000);
+        deposit(&mut bob, &mut state, 1_000);
+        state.accrue_reward(3_000).unwrap();
+
+        // locked deposit can't lock funds of others
+        alice.refresh_reserve(&mut state).unwrap();
+        bob.refresh_reserve(&mut state).unwrap();
+        assert!(alice.clone().transfer_to(&mut bob, &mut state, 10).is_err());
+
+        // lock carries over into an empty reserve
+        carol.refresh_reserve(&mut state).unwrap();
+        alice.transfer_to(&mut carol, &mut state, 10).unwrap();
+        assert_eq!(carol.deposit_amount, 1_000);
+        assert_eq!(carol.reward_amount, 2_000);
+        assert_eq!(carol.unlock_time, LOCK_TIERS[3].0);
+        assert_eq!(carol.lock_weight, LOCK_TIERS[3].1);
+        assert!(alice.is_empty());
+        assert!(!alice.is_locked(10));
+        assert_eq!(state.deposit_amount, 2_000);
+        assert_eq!(state.weighted_deposit_amount, 3_000);
+
+        // rewards keep accruing at the carried weight
+        state.accrue_reward(3_000).unwrap();
+        carol.refresh_reserve(&mut state).unwrap();
+        assert_eq!(carol.reward_amount, 4_000);
+
+        // unlocked deposit merges into a reserve with deposit
+        bob.refresh_reserve(&mut state).unwrap();
+        bob.transfer_to(&mut carol, &mut state, 20).unwrap();
+        assert_eq!(carol.deposit_amount, 2_000);
+        assert_eq!(carol.reward_amount, 6_000);
+        assert!(bob.is_empty());
+        assert_eq!(state.weighted_deposit_amount, 4_000);
+    }
+
+    #[test]
+    fn test_share_exchange_rate() {
+        let mut state = new_state();
+
+        // 1:1 before any rewards
+        assert_eq!(state.shares_for(1_000).unwrap(), 1_000);
+        state.total_shares = 1_000;
+        state.total_underlying = 1_000;
+
+        // rewards compound into the exchange rate
+        state.accrue_reward(500).unwrap();
+        assert_eq!(state.total_underlying, 1_500);
+        assert_eq!(state.reward_amount, 0);
+        assert_eq!(state.underlying_for(1_000).unwrap(), 1_500);
+
+        // later depositor gets fewer shares, rounded down
+        assert_eq!(state.shares_for(1_000).unwrap(), 666);
+        assert_eq!(state.underlying_for(1).unwrap(), 1);
+
+        // dust rounds down to nothing, rejected by deposit_shares / withdraw_shares
+        state.total_underlying = 3_000;
+        assert_eq!(state.shares_for(2).unwrap(), 0);
+        state.total_underlying = 500;
+        assert_eq!(state.underlying_for(1).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_reward_split_between_reserves_and_shares() {
+        let mut state = new_state();
+        let mut reserve = new_reserve(&state);
+        deposit(&mut reserve, &mut state, 3_000);
+        state.total_shares = 1_000;
+        state.total_underlying = 1_000;
+
+        state.accrue_reward(4_000).unwrap();
+        assert_eq!(state.total_underlying, 2_000);
+        assert_eq!(state.reward_amount, 3_000);
+
+        reserve.refresh_reserve(&mut state).unwrap();
+        assert_eq!(reserve.reward_amount, 3_000);
+        assert_eq!(state.reward_amount, 0);
+    }
+
+    #[test]
+    fn test_uncapped_market() {
+        let mut state = new_state();
+        let mut market = new_market();
+
+        market.add_outstanding(&mut state, u64::MAX).unwrap();
+        assert_eq!(market.outstanding_amount, u64::MAX);
+        assert_eq!(state.outstanding_amount, u64::MAX);
+    }
+
+    #[test]
+    fn test_market_and_global_caps() {
+        let mut state = new_state();
+        let mut market_a = new_market();
+        let mut market_b = new_market();
+        market_a.supply_cap = 100;
+        state.global_cap = 150;
+
+        // market cap
+        market_a.add_outstanding(&mut state, 100).unwrap();
+        assert!(market_a.add_outstanding(&mut state, 1).is_err());
+
+        // global cap
+        market_b.add_outstanding(&mut state, 50).unwrap();
+        assert!(market_b.add_outstanding(&mut state, 1).is_err());
+        assert_eq!(state.outstanding_amount, 150);
+
+        // redeemed amount frees room
+        market_a.sub_outstanding(&mut state, 40);
+        market_b.add_outstanding(&mut state, 40).unwrap();
+        assert_eq!(market_a.outstanding_amount, 60);
+        assert_eq!(market_b.outstanding_amount, 90);
+        assert_eq!(state.outstanding_amount, 150);
+    }
+
+    #[test]
+    fn test_untracked_redemption_saturates() {
+        let mut state = new_state();
+        let mut market = new_market();
+
+        market.add_outstanding(&mut state, 10).unwrap();
+        market.sub_outstanding(&mut state, 1_000);
+        assert_eq!(market.outstanding_amount, 0);
+        assert_eq!(state.outstanding_amount, 0);
+    }
+
+    #[test]
+    fn test_redeem_fee_fixed_at_queue_time() {
+        let mut state = new_state();
+        let mut market = new_market();
+        market.add_outstanding(&mut state, 1_000).unwrap();
+        market.withdrawal_liq = 1_000;
+        market.queued_amount = 1_000;
+        state.queued_redeem_amount = 1_000;
+
+        let ticket = RedeemTicket {
+            nonce: 0,
+            owner: Pubkey::default(),
+            market: Pubkey::default(),
+            queue_id: 0,
+            ousd_amount: 1_000,
+            queue_time: 0,
+            fee_bps: 10,
+            escrowed_amount: 0,
+        };
+
+        // raising the market fee after queueing does not apply to the ticket
+        market.redeem_fee_bps = 1_000;
+        let (fee_amount, ousd_amount) = market.settle_redemption(&mut state, &ticket, 0).unwrap();
+        assert_eq!(fee_amount, 1);
+        assert_eq!(ousd_amount, 999);
+        assert_eq!(market.withdrawal_liq, 1);
+        assert_eq!(market.outstanding_amount, 1);
+        assert_eq!(market.queued_amount, 0);
+        assert_eq!(state.queued_redeem_amount, 0);
+    }
+
+    #[test]
+    fn test_flash_mint_config() {
+        let mut state = new_state();
+
+        assert!(state
+            .apply_flash_mint(&UpdateFlashMintArgs {
+                flash_mint_cap: 1_000,
+                flash_fee_bps: MAX_FEE_BPS + 1,
+            })
+            .is_err());
+
+        state
+            .apply_flash_mint(&UpdateFlashMintArgs {
+                flash_mint_cap: 1_000,
+                flash_fee_bps: 9,
+            })
+            .unwrap();
+        assert_eq!(state.flash_mint_cap, 1_000);
+
+        // fee rounds up
+        assert_eq!(state.flash_fee(1_000).unwrap(), 1);
+        assert_eq!(state.flash_fee(20_000).unwrap(), 18);
+    }
+
+    #[test]
+    fn test_timelock_action_validate() {
+        // queued actions share the checks of their direct instructions
+        assert!(TimelockAction::SetTimelockDelay {
+            delay: MAX_TIMELOCK_DELAY + 1,
+        }
+        .validate()
+        .is_err());
+        assert!(TimelockAction::UpdateFees {
+            market: Pubkey::default(),
+            args: UpdateFeesArgs {
+                mint_fee_bps: 0,
+                redeem_fee_bps: 0,
+                swap_fee_bps: MAX_FEE_BPS + 1,
+            },
+        }
+        .validate()
+        .is_err());
+        assert!(TimelockAction::UpdateRateLimits {
+            market: Pubkey::default(),
+            args: UpdateRateLimitsArgs {
+                window: -1,
+                mint_limit: 0,
+                redeem_limit: 0,
+            },
+        }
+        .validate()
+        .is_err());
+
+        TimelockAction::SetTimelockDelay {
+            delay: MAX_TIMELOCK_DELAY,
+        }
+        .validate()
+        .unwrap();
+        TimelockAction::UpdateTreasury {
+            treasury: Pubkey::default(),
+        }
+        .validate()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_flash_discriminators() {
+        use anchor_lang::InstructionData;
+
+        let flash_mint = crate::instruction::FlashMint {
+            args: FlashMintArgs { amount: 1 },
+        }
+        .data();
+        assert_eq!(flash_mint[..8], FLASH_MINT_DISCRIMINATOR);
+
+        let flash_repay = crate::instruction::FlashRepay {
+            args: FlashRepayArgs {
+                flash_mint_index: 3,
+            },
+        }
+        .data();
+        assert_eq!(flash_repay[..8], FLASH_REPAY_DISCRIMINATOR);
+        assert_eq!(
+            FlashRepayArgs::try_from_slice(&flash_repay[8..]).unwrap(),
+            FlashRepayArgs {
+                flash_mint_index: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_swap_moves_outstanding() {
+        let mut market_in = new_market();
+        let mut market_out = new_market();
+        market_in.supply_cap = 1_500;
+        market_in.outstanding_amount = 1_000;
+        market_out.outstanding_amount = 300;
+
+        // input market cap
+        assert!(market_out.move_outstanding(&mut market_in, 501).is_err());
+
+        // untracked backing of output market saturates
+        market_out.move_outstanding(&mut market_in, 500).unwrap();
+        assert_eq!(market_in.outstanding_amount, 1_500);
+        assert_eq!(market_out.outstanding_amount, 0);
+    }
+
+    #[test]
+    fn test_rate_limits_disabled() {
+        let mut market = new_market();
+
+        market.add_mint_volume(u64::MAX, 0).unwrap();
+        market.add_redeem_volume(u64::MAX, 0).unwrap();
+        assert_eq!(market.mint_volume, 0);
+        assert_eq!(market.redeem_volume, 0);
+    }
+
+    #[test]
+    fn test_rolling_mint_limit() {
+        let mut market = new_market();
+        market
+            .apply_rate_limits(&UpdateRateLimitsArgs {
+                window: 3_600,
+                mint_limit: 1_000,
+                redeem_limit: 0,
+            })
+            .unwrap();
+
+        // limit reached within a window
+        market.add_mint_volume(1_000, 0).unwrap();
+        assert!(market.add_mint_volume(1, 0).is_err());
+
+        // a quarter of the limit is released after a quarter of the window
+        let mut decayed = market.clone();
+        decayed.add_mint_volume(250, 900).unwrap();
+        assert_eq!(decayed.mint_volume, 1_000);
+        assert!(decayed.add_mint_volume(1, 900).is_err());
+
+        // the whole limit is released after a window
+        market.add_mint_volume(1_000, 3_600).unwrap();
+        assert_eq!(market.mint_volume, 1_000);
+
+        // unlimited redeem volume
+        market.add_redeem_volume(u64::MAX, 3_600).unwrap();
+    }
+
+    #[test]
+    fn test_rolling_redeem_limit() {
+        let mut market = new_market();
+        market
+            .apply_rate_limits(&UpdateRateLimitsArgs {
+                window: 100,
+                mint_limit: 0,
+                redeem_limit: 10,
+            })
+            .unwrap();
+
+        market.add_redeem_volume(10, 1_000).unwrap();
+        assert!(market.clone().add_redeem_volume(1, 1_009).is_err());
+        market.add_redeem_volume(1, 1_010).unwrap();
+        assert_eq!(market.redeem_volume, 10);
+    }
+
+    #[test]
+    fn test_oracle_config() {
+        let mut market = new_market();
+        let args = UpdateOracleArgs {
+            oracle: Pubkey::new_unique(),
+            oracle_source: ORACLE_SOURCE_PYTH,
+            min_price_bps: 9_900,
+            max_price_age: 60,
+        };
+
+        assert!(market
+            .apply_oracle(&UpdateOracleArgs {
+                oracle_source: 3,
+                ..args
+            })
+            .is_err());
+        assert!(market
+            .apply_oracle(&UpdateOracleArgs {
+                oracle: Pubkey::default(),
+                ..args
+            })
+            .is_err());
+        assert!(market
+            .apply_oracle(&UpdateOracleArgs {
+                min_price_bps: 10_001,
+                ..args
+            })
+            .is_err());
+        assert!(market
+            .apply_oracle(&UpdateOracleArgs {
+                max_price_age: 0,
+                ..args
+            })
+            .is_err());
+
+        market.apply_oracle(&args).unwrap();
+        assert_eq!(market.oracle, args.oracle);
+
+        // disabling clears the price feed
+        market
+            .apply_oracle(&UpdateOracleArgs {
+                oracle_source: ORACLE_SOURCE_NONE,
+                ..args
+            })
+            .unwrap();
+        assert_eq!(market.oracle, Pubkey::default());
+        market.check_price(&[], 0).unwrap();
+    }
+
+    #[test]
+    fn test_depeg_guard() {
+        let mut market = new_market();
+        let oracle = Pubkey::new_unique();
+        market
+            .apply_oracle(&UpdateOracleArgs {
+                oracle,
+                oracle_source: ORACLE_SOURCE_PYTH,
+                min_price_bps: 9_900,
+                max_price_age: 60,
+            })
+            .unwrap();
+
+        let check = |price: i64, now: i64, key: &Pubkey| {
+            let mut data =
+                crate::oracle::tests::pyth_account(price, -8, PYTH_STATUS_TRADING, 1_000);
+            let mut lamports = 0;
+            let owner = Pubkey::default();
+            let oracle_info = AccountInfo::new(
+                key,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                &owner,
+                false,
+                0,
+            );
+            market.check_price(&[oracle_info], now)
+        };
+
+        // $0.995 fresh price
+        check(99_500_000, 1_060, &oracle).unwrap();
+
+        // stale price
+        assert!(check(99_500_000, 1_061, &oracle).is_err());
+
+        // below threshold
+        assert!(check(98_990_000, 1_000, &oracle).is_err());
+
+        // missing price feed
+        assert!(check(99_500_000, 1_000, &Pubkey::new_unique()).is_err());
+    }
+}
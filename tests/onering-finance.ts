@@ -8,23 +8,31 @@ import {
   LAMPORTS_PER_SOL,
   SystemProgram,
   SYSVAR_RENT_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
 } from "@solana/web3.js";
 import { TOKEN_PROGRAM_ID, Token } from "@solana/spl-token";
 import { OneringFinance } from "../target/types/onering_finance";
 
 // PDA seeds
 const WRONG_SEED = "wrong_seed";
+const STATE_SEED = "or_state";
+const MARKET_SEED = "or_market";
 const OUSD_MINT_AUTH_SEED = "or_ousd_mint_auth";
 const STABLE_VAULT_SEED = "or_stable_vault";
 const RESERVE_SEED = "or_reserve";
+const REDEEM_TICKET_SEED = "or_redeem_ticket";
+const QUEUED_ACTION_SEED = "or_queued_action";
+
+const PAUSE_MINT = 1 << 0;
+const PAUSE_ALL = (1 << 7) - 1;
 
 // main state & 1USD mint
-const STATE_KEYPAIR = Keypair.generate();
+let statePda: PublicKey, stateBump: number;
 let ousdMint: Token;
 let ousdMintAuthPda: PublicKey, ousdMintAuthBump: number;
 
 // market, stable mint, stable vault
-const MARKET_KEYPAIR = Keypair.generate();
+let marketPda: PublicKey, marketBump: number;
 const STABLE_MINT_AUTH_KEYPAIR = Keypair.generate();
 let stableMint: Token;
 let stableVaultPda: PublicKey, stableVaultBump: number;
@@ -39,12 +47,41 @@ const USER_KEYPAIR = Keypair.generate();
 
 // amounts
 const DEPOSIT_AMOUNT = new BN("100000000");
+const SWAP_AMOUNT = DEPOSIT_AMOUNT.divn(10);
+
+// fees
+const FEE_BPS = 50;
+const SWAP_FEE_BPS = 30;
+let treasuryOusdToken: PublicKey;
+
+// harvested yield
+const HARVEST_AMOUNT = new BN("1000000");
+let adminStableToken: PublicKey;
 
 // reserve
 let reservePda: PublicKey, reserveBump: number;
 let initializerStableToken: PublicKey, initializerOusdToken: PublicKey;
 
+// redemption ticket
+let ticketPda: PublicKey, ticketBump: number;
+
 describe("onering-finance", () => {
+  // redemption ticket PDA, one per queue id of the market
+  const findTicketPda = (
+    owner: PublicKey,
+    market: PublicKey,
+    queueId: number
+  ) =>
+    PublicKey.findProgramAddress(
+      [
+        owner.toBuffer(),
+        Buffer.from(anchor.utils.bytes.utf8.encode(REDEEM_TICKET_SEED)),
+        market.toBuffer(),
+        new BN(queueId).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+
   // Configure the client to use the local cluster.
   const provider = anchor.Provider.env();
   anchor.setProvider(provider);
@@ -87,23 +124,41 @@ describe("onering-finance", () => {
       [FEE_PAYER_KEYPAIR]
     );
 
+    // create 1USD token mint
+    ousdMint = await Token.createMint(
+      provider.connection,
+      FEE_PAYER_KEYPAIR,
+      FEE_PAYER_KEYPAIR.publicKey,
+      null,
+      9,
+      TOKEN_PROGRAM_ID
+    );
+
+    // main state PDA
+    [statePda, stateBump] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from(anchor.utils.bytes.utf8.encode(STATE_SEED)),
+        ousdMint.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
     // 1USD mint authority (PDA)
     [ousdMintAuthPda, ousdMintAuthBump] = await PublicKey.findProgramAddress(
       [
         Buffer.from(anchor.utils.bytes.utf8.encode(OUSD_MINT_AUTH_SEED)),
-        STATE_KEYPAIR.publicKey.toBuffer(),
+        statePda.toBuffer(),
       ],
       program.programId
     );
 
-    // create 1USD token mint
-    ousdMint = await Token.createMint(
-      provider.connection,
-      FEE_PAYER_KEYPAIR,
+    // hand 1USD mint authority over to the PDA
+    await ousdMint.setAuthority(
+      ousdMint.publicKey,
       ousdMintAuthPda,
-      null,
-      9,
-      TOKEN_PROGRAM_ID
+      "MintTokens",
+      FEE_PAYER_KEYPAIR,
+      []
     );
 
     // initializer 1USD token ATA
@@ -111,6 +166,11 @@ describe("onering-finance", () => {
       USER_KEYPAIR.publicKey
     );
 
+    // treasury 1USD token ATA
+    treasuryOusdToken = await ousdMint.createAssociatedTokenAccount(
+      ADMIN_KEYPAIR.publicKey
+    );
+
     // create stable token mint
     stableMint = await Token.createMint(
       provider.connection,
@@ -134,12 +194,27 @@ describe("onering-finance", () => {
       DEPOSIT_AMOUNT.toNumber()
     );
 
+    // admin stable token ATA, harvested yield
+    adminStableToken = await stableMint.createAssociatedTokenAccount(
+      ADMIN_KEYPAIR.publicKey
+    );
+
+    // market PDA
+    [marketPda, marketBump] = await PublicKey.findProgramAddress(
+      [
+        statePda.toBuffer(),
+        Buffer.from(anchor.utils.bytes.utf8.encode(MARKET_SEED)),
+        stableMint.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+
     // stable vault
     [stableVaultPda, stableVaultBump] = await PublicKey.findProgramAddress(
       [
         stableMint.publicKey.toBuffer(),
         Buffer.from(anchor.utils.bytes.utf8.encode(STABLE_VAULT_SEED)),
-        MARKET_KEYPAIR.publicKey.toBuffer(),
+        marketPda.toBuffer(),
       ],
       program.programId
     );
@@ -149,7 +224,7 @@ describe("onering-finance", () => {
       await PublicKey.findProgramAddress(
         [
           Buffer.from(anchor.utils.bytes.utf8.encode(STABLE_VAULT_SEED)),
-          STATE_KEYPAIR.publicKey.toBuffer(),
+          statePda.toBuffer(),
         ],
         program.programId
       );
@@ -157,7 +232,7 @@ describe("onering-finance", () => {
     [wrongStableVaultAuthPda] = await PublicKey.findProgramAddress(
       [
         Buffer.from(anchor.utils.bytes.utf8.encode(WRONG_SEED)),
-        STATE_KEYPAIR.publicKey.toBuffer(),
+        statePda.toBuffer(),
       ],
       program.programId
     );
@@ -167,15 +242,59 @@ describe("onering-finance", () => {
       [
         USER_KEYPAIR.publicKey.toBuffer(),
         Buffer.from(anchor.utils.bytes.utf8.encode(RESERVE_SEED)),
-        STATE_KEYPAIR.publicKey.toBuffer(),
+        statePda.toBuffer(),
       ],
       program.programId
     );
   });
 
   it("should create an admin", async () => {
+    // 1USD mint must be minted by the mint authority PDA only
+    const [wrongStatePda, wrongStateBump] = await PublicKey.findProgramAddress(
+      [
+        Buffer.from(anchor.utils.bytes.utf8.encode(STATE_SEED)),
+        stableMint.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const [wrongMintAuthPda, wrongMintAuthBump] =
+      await PublicKey.findProgramAddress(
+        [
+          Buffer.from(anchor.utils.bytes.utf8.encode(OUSD_MINT_AUTH_SEED)),
+          wrongStatePda.toBuffer(),
+        ],
+        program.programId
+      );
+    try {
+      await program.rpc.createAdmin(
+        {
+          stateBump: wrongStateBump,
+          ousdMintAuthBump: wrongMintAuthBump,
+          stableVaultAuthBump,
+        },
+        {
+          accounts: {
+            admin: ADMIN_KEYPAIR.publicKey,
+            ousdMint: stableMint.publicKey,
+            ousdMintAuth: wrongMintAuthPda,
+            treasury: treasuryOusdToken,
+            state: wrongStatePda,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [ADMIN_KEYPAIR],
+        }
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(
+        err.msg,
+        "1USD mint authority is not the mint authority PDA"
+      );
+    }
+
     await program.rpc.createAdmin(
       {
+        stateBump,
         ousdMintAuthBump,
         stableVaultAuthBump,
       },
@@ -183,26 +302,28 @@ describe("onering-finance", () => {
         accounts: {
           admin: ADMIN_KEYPAIR.publicKey,
           ousdMint: ousdMint.publicKey,
-          state: STATE_KEYPAIR.publicKey,
+          ousdMintAuth: ousdMintAuthPda,
+          treasury: treasuryOusdToken,
+          state: statePda,
+          systemProgram: SystemProgram.programId,
         },
-        instructions: [
-          await program.account.state.createInstruction(STATE_KEYPAIR),
-        ],
-        signers: [ADMIN_KEYPAIR, STATE_KEYPAIR],
+        signers: [ADMIN_KEYPAIR],
       }
     );
 
     // asserts
-    const state = await program.account.state.fetch(STATE_KEYPAIR.publicKey);
+    const state = await program.account.state.fetch(statePda);
     assert.ok(state.admin.equals(ADMIN_KEYPAIR.publicKey));
     assert.ok(state.ousdMint.equals(ousdMint.publicKey));
-    assert.ok(!state.emergencyFlag);
+    assert.ok(state.treasury.equals(treasuryOusdToken));
+    assert.equal(state.pauseFlags, 0);
   });
 
   it("should create a market (stable token pool)", async () => {
     try {
       await program.rpc.createMarket(
         {
+          marketBump,
           stableVaultBump,
         },
         {
@@ -211,16 +332,13 @@ describe("onering-finance", () => {
             stableMint: stableMint.publicKey,
             stableVault: stableVaultPda,
             stableVaultAuth: wrongStableVaultAuthPda,
-            market: MARKET_KEYPAIR.publicKey,
-            state: STATE_KEYPAIR.publicKey,
+            market: marketPda,
+            state: statePda,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
             rent: SYSVAR_RENT_PUBKEY,
           },
-          instructions: [
-            await program.account.market.createInstruction(MARKET_KEYPAIR),
-          ],
-          signers: [ADMIN_KEYPAIR, MARKET_KEYPAIR],
+          signers: [ADMIN_KEYPAIR],
         }
       );
       assert.fail();
@@ -231,6 +349,7 @@ describe("onering-finance", () => {
 
     await program.rpc.createMarket(
       {
+        marketBump,
         stableVaultBump,
       },
       {
@@ -239,31 +358,231 @@ describe("onering-finance", () => {
           stableMint: stableMint.publicKey,
           stableVault: stableVaultPda,
           stableVaultAuth: stableVaultAuthPda,
-          market: MARKET_KEYPAIR.publicKey,
-          state: STATE_KEYPAIR.publicKey,
+          market: marketPda,
+          state: statePda,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           rent: SYSVAR_RENT_PUBKEY,
         },
-        instructions: [
-          await program.account.market.createInstruction(MARKET_KEYPAIR),
-        ],
-        signers: [ADMIN_KEYPAIR, MARKET_KEYPAIR],
+        signers: [ADMIN_KEYPAIR],
       }
     );
 
     // asserts
-    const market = await program.account.market.fetch(MARKET_KEYPAIR.publicKey);
+    const market = await program.account.market.fetch(marketPda);
     assert.ok(market.stableMint.equals(stableMint.publicKey));
     assert.ok(market.stableVaultBump === stableVaultBump);
     assert.ok(market.withdrawalLiq.eq(new BN("0")));
     assert.ok(!market.lockFlag);
   });
 
+  it("should update a market", async () => {
+    const updateMarket = (
+      lockFlag: boolean,
+      withdrawalLiq: BN,
+      incrementWithdrawalLiq: boolean,
+      admin: Keypair = ADMIN_KEYPAIR
+    ) =>
+      program.rpc.updateMarket(
+        {
+          lockFlag,
+          withdrawalLiq,
+          incrementWithdrawalLiq,
+        },
+        {
+          accounts: {
+            admin: admin.publicKey,
+            market: marketPda,
+            state: statePda,
+          },
+          signers: [admin],
+        }
+      );
+
+    try {
+      await updateMarket(true, new BN("0"), false, NEW_ADMIN_KEYPAIR);
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.code, 6000);
+      assert.equal(err.msg, "Access denied");
+    }
+
+    // lock
+    await updateMarket(true, new BN("0"), false);
+    let market = await program.account.market.fetch(marketPda);
+    assert.ok(market.lockFlag);
+    assert.ok(market.withdrawalLiq.eq(new BN("0")));
+
+    // unlock & set withdrawal liquidity
+    await updateMarket(false, DEPOSIT_AMOUNT.muln(500), false);
+    market = await program.account.market.fetch(marketPda);
+    assert.ok(!market.lockFlag);
+    assert.ok(market.withdrawalLiq.eq(DEPOSIT_AMOUNT.muln(500)));
+
+    // increment withdrawal liquidity
+    await updateMarket(false, DEPOSIT_AMOUNT.muln(500), true);
+    market = await program.account.market.fetch(marketPda);
+    assert.ok(!market.lockFlag);
+    assert.ok(market.withdrawalLiq.eq(DEPOSIT_AMOUNT.muln(1_000)));
+  });
+
+  it("should update fees", async () => {
+    const updateFees = (mintFeeBps: number, redeemFeeBps: number) =>
+      program.rpc.updateFees(
+        {
+          mintFeeBps,
+          redeemFeeBps,
+          swapFeeBps: 0,
+        },
+        {
+          accounts: {
+            admin: ADMIN_KEYPAIR.publicKey,
+            market: marketPda,
+            state: statePda,
+          },
+          signers: [ADMIN_KEYPAIR],
+        }
+      );
+
+    try {
+      await updateFees(101, 0);
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Fee exceeds the upper bound");
+    }
+
+    await updateFees(0, 0);
+
+    // treasury is set on main state, for every market
+    await program.rpc.updateTreasury({
+      accounts: {
+        admin: ADMIN_KEYPAIR.publicKey,
+        treasury: treasuryOusdToken,
+        state: statePda,
+      },
+      signers: [ADMIN_KEYPAIR],
+    });
+
+    // asserts
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.mintFeeBps, 0);
+    assert.equal(market.redeemFeeBps, 0);
+    const state = await program.account.state.fetch(statePda);
+    assert.ok(state.treasury.equals(treasuryOusdToken));
+  });
+
   it("should mint 100 $1USD", async () => {
+    const mintOusdAccounts = {
+      accounts: {
+        initializer: USER_KEYPAIR.publicKey,
+        stableMint: stableMint.publicKey,
+        stableVault: stableVaultPda,
+        initializerStableToken,
+        ousdMint: ousdMint.publicKey,
+        ousdMintAuth: ousdMintAuthPda,
+        initializerOusdToken,
+        treasuryOusdToken,
+        market: marketPda,
+        state: statePda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+      signers: [USER_KEYPAIR],
+    };
+
+    try {
+      await program.rpc.mintOusd(
+        {
+          amount: DEPOSIT_AMOUNT,
+          minAmountOut: DEPOSIT_AMOUNT.muln(1_000).addn(1),
+          expiry: null,
+        },
+        mintOusdAccounts
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Slippage exceeded");
+    }
+
+    try {
+      await program.rpc.mintOusd(
+        {
+          amount: DEPOSIT_AMOUNT,
+          minAmountOut: new BN("0"),
+          expiry: new BN("1"),
+        },
+        mintOusdAccounts
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Transaction expired");
+    }
+
+    // market cap just below the minted amount
+    const updateCaps = (supplyCap: BN, globalCap: BN) =>
+      program.rpc.updateCaps(
+        { supplyCap, globalCap },
+        {
+          accounts: {
+            admin: ADMIN_KEYPAIR.publicKey,
+            market: marketPda,
+            state: statePda,
+          },
+          signers: [ADMIN_KEYPAIR],
+        }
+      );
+    await updateCaps(DEPOSIT_AMOUNT.muln(1_000).subn(1), new BN("0"));
+    try {
+      await program.rpc.mintOusd(
+        {
+          amount: DEPOSIT_AMOUNT,
+          minAmountOut: new BN("0"),
+          expiry: null,
+        },
+        mintOusdAccounts
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Mint cap exceeded");
+    }
+    await updateCaps(new BN("0"), new BN("0"));
+
+    // hourly mint limit just below the minted amount
+    const updateRateLimits = (mintLimit: BN) =>
+      program.rpc.updateRateLimits(
+        { window: new BN("3600"), mintLimit, redeemLimit: new BN("0") },
+        {
+          accounts: {
+            admin: ADMIN_KEYPAIR.publicKey,
+            market: marketPda,
+            state: statePda,
+          },
+          signers: [ADMIN_KEYPAIR],
+        }
+      );
+    await updateRateLimits(DEPOSIT_AMOUNT.muln(1_000).subn(1));
+    try {
+      await program.rpc.mintOusd(
+        {
+          amount: DEPOSIT_AMOUNT,
+          minAmountOut: new BN("0"),
+          expiry: null,
+        },
+        mintOusdAccounts
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(
+        err.msg,
+        "Mint volume exceeds the rate limit of the current window"
+      );
+    }
+    await updateRateLimits(new BN("0"));
+
     await program.rpc.mintOusd(
       {
         amount: DEPOSIT_AMOUNT,
+        minAmountOut: new BN("0"),
+        expiry: null,
       },
       {
         accounts: {
@@ -274,8 +593,9 @@ describe("onering-finance", () => {
           ousdMint: ousdMint.publicKey,
           ousdMintAuth: ousdMintAuthPda,
           initializerOusdToken,
-          market: MARKET_KEYPAIR.publicKey,
-          state: STATE_KEYPAIR.publicKey,
+          treasuryOusdToken,
+          market: marketPda,
+          state: statePda,
           tokenProgram: TOKEN_PROGRAM_ID,
         },
         signers: [USER_KEYPAIR],
@@ -293,12 +613,54 @@ describe("onering-finance", () => {
       initializerOusdToken
     );
     assert.ok(initializerOusdTokenAccount.amount.eq(DEPOSIT_AMOUNT.muln(1_000)));
+    const market = await program.account.market.fetch(marketPda);
+    assert.ok(market.outstandingAmount.eq(DEPOSIT_AMOUNT.muln(1_000)));
+    const state = await program.account.state.fetch(statePda);
+    assert.ok(state.outstandingAmount.eq(DEPOSIT_AMOUNT.muln(1_000)));
   });
 
   it("should deposit (old stake) 100 $1USD", async () => {
+    try {
+      await program.rpc.deposit(
+        {
+          amount: DEPOSIT_AMOUNT.muln(1_000),
+          lockTier: 4,
+        },
+        {
+          accounts: {
+            initializer: USER_KEYPAIR.publicKey,
+            ousdMint: ousdMint.publicKey,
+            initializerOusdToken,
+            reserve: reservePda,
+            state: statePda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          },
+          instructions: [
+            // create deposit reserve PDA
+            await program.instruction.createReserve(
+              { nonce: reserveBump },
+              {
+                accounts: {
+                  initializer: USER_KEYPAIR.publicKey,
+                  reserve: reservePda,
+                  state: statePda,
+                  systemProgram: SystemProgram.programId,
+                },
+              }
+            ),
+          ],
+          signers: [USER_KEYPAIR],
+        }
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Lock tier is invalid");
+    }
+
     await program.rpc.deposit(
       {
         amount: DEPOSIT_AMOUNT.muln(1_000),
+        lockTier: 0,
       },
       {
         accounts: {
@@ -306,7 +668,7 @@ describe("onering-finance", () => {
           ousdMint: ousdMint.publicKey,
           initializerOusdToken,
           reserve: reservePda,
-          state: STATE_KEYPAIR.publicKey,
+          state: statePda,
           tokenProgram: TOKEN_PROGRAM_ID,
         },
         instructions: [
@@ -317,7 +679,7 @@ describe("onering-finance", () => {
               accounts: {
                 initializer: USER_KEYPAIR.publicKey,
                 reserve: reservePda,
-                state: STATE_KEYPAIR.publicKey,
+                state: statePda,
                 systemProgram: SystemProgram.programId,
               },
             }
@@ -332,8 +694,11 @@ describe("onering-finance", () => {
     assert.ok(reserve.nonce === reserveBump);
     assert.ok(reserve.depositAmount.eq(DEPOSIT_AMOUNT.muln(1_000)));
     assert.ok(!reserve.freezeFlag);
-    const state = await program.account.state.fetch(STATE_KEYPAIR.publicKey);
+    assert.ok(reserve.unlockTime.eq(new BN("0")));
+    assert.ok(reserve.lockWeight === 10_000);
+    const state = await program.account.state.fetch(statePda);
     assert.ok(state.depositAmount.eq(DEPOSIT_AMOUNT.muln(1_000)));
+    assert.ok(state.weightedDepositAmount.eq(DEPOSIT_AMOUNT.muln(1_000)));
     const initializerOusdTokenAccount = await ousdMint.getAccountInfo(
       initializerOusdToken
     );
@@ -351,8 +716,9 @@ describe("onering-finance", () => {
           ousdMint: ousdMint.publicKey,
           ousdMintAuth: ousdMintAuthPda,
           initializerOusdToken,
+          treasuryOusdToken,
           reserve: reservePda,
-          state: STATE_KEYPAIR.publicKey,
+          state: statePda,
           tokenProgram: TOKEN_PROGRAM_ID,
         },
         signers: [USER_KEYPAIR],
@@ -364,7 +730,7 @@ describe("onering-finance", () => {
     assert.ok(reserve.nonce === reserveBump);
     assert.ok(reserve.depositAmount.eq(new BN("0")));
     assert.ok(!reserve.freezeFlag);
-    const state = await program.account.state.fetch(STATE_KEYPAIR.publicKey);
+    const state = await program.account.state.fetch(statePda);
     assert.ok(state.depositAmount.eq(new BN("0")));
     const initializerOusdTokenAccount = await ousdMint.getAccountInfo(
       initializerOusdToken
@@ -372,11 +738,166 @@ describe("onering-finance", () => {
     assert.ok(initializerOusdTokenAccount.amount.eq(DEPOSIT_AMOUNT.muln(1_000)));
   });
 
+  it("should withdraw 100 $1USD after the cooldown or with an early exit fee", async () => {
+    const EARLY_EXIT_FEE_BPS = 100;
+    const updateWithdrawCooldown = (
+      withdrawCooldown: number,
+      earlyExitFeeBps: number
+    ) =>
+      program.rpc.updateWithdrawCooldown(
+        {
+          withdrawCooldown: new BN(withdrawCooldown),
+          earlyExitFeeBps,
+        },
+        {
+          accounts: {
+            admin: ADMIN_KEYPAIR.publicKey,
+            state: statePda,
+          },
+          signers: [ADMIN_KEYPAIR],
+        }
+      );
+    const withdrawAccounts = {
+      accounts: {
+        initializer: USER_KEYPAIR.publicKey,
+        ousdMint: ousdMint.publicKey,
+        ousdMintAuth: ousdMintAuthPda,
+        initializerOusdToken,
+        treasuryOusdToken,
+        reserve: reservePda,
+        state: statePda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+      signers: [USER_KEYPAIR],
+    };
+
+    await updateWithdrawCooldown(1, EARLY_EXIT_FEE_BPS);
+    let state = await program.account.state.fetch(statePda);
+    assert.ok(state.withdrawCooldown.eq(new BN(1)));
+    assert.ok(state.earlyExitFeeBps === EARLY_EXIT_FEE_BPS);
+
+    await program.rpc.deposit(
+      {
+        amount: DEPOSIT_AMOUNT.muln(1_000),
+        lockTier: 0,
+      },
+      {
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          ousdMint: ousdMint.publicKey,
+          initializerOusdToken,
+          reserve: reservePda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [USER_KEYPAIR],
+      }
+    );
+
+    // half after the cooldown
+    await program.rpc.requestWithdraw(
+      {
+        amount: DEPOSIT_AMOUNT.muln(500),
+      },
+      {
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          reserve: reservePda,
+          state: statePda,
+        },
+        signers: [USER_KEYPAIR],
+      }
+    );
+
+    let reserve = await program.account.reserve.fetch(reservePda);
+    assert.ok(reserve.depositAmount.eq(DEPOSIT_AMOUNT.muln(500)));
+    assert.ok(reserve.pendingAmount.eq(DEPOSIT_AMOUNT.muln(500)));
+    assert.ok(reserve.withdrawableTime.gtn(0));
+
+    const completeWithdrawAccounts = {
+      accounts: {
+        initializer: USER_KEYPAIR.publicKey,
+        ousdMint: ousdMint.publicKey,
+        ousdMintAuth: ousdMintAuthPda,
+        initializerOusdToken,
+        reserve: reservePda,
+        state: statePda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+      signers: [USER_KEYPAIR],
+    };
+    try {
+      await program.rpc.completeWithdraw(completeWithdrawAccounts);
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Withdraw cooldown is not over");
+    }
+
+    // wait out the cooldown
+    await new Promise((resolve) => setTimeout(resolve, 3_000));
+    await program.rpc.completeWithdraw(completeWithdrawAccounts);
+
+    try {
+      await program.rpc.completeWithdraw(completeWithdrawAccounts);
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "No pending withdrawal");
+    }
+
+    // rest instantly, early exit fee is sent to treasury
+    await program.rpc.withdraw(
+      {
+        amount: DEPOSIT_AMOUNT.muln(500),
+      },
+      withdrawAccounts
+    );
+
+    // asserts
+    const earlyExitFee = DEPOSIT_AMOUNT.muln(500)
+      .muln(EARLY_EXIT_FEE_BPS)
+      .addn(9_999)
+      .divn(10_000);
+    reserve = await program.account.reserve.fetch(reservePda);
+    assert.ok(reserve.depositAmount.eq(new BN("0")));
+    assert.ok(reserve.pendingAmount.eq(new BN("0")));
+    state = await program.account.state.fetch(statePda);
+    assert.ok(state.depositAmount.eq(new BN("0")));
+    assert.ok(state.pendingWithdrawAmount.eq(new BN("0")));
+    const treasuryOusdTokenAccount = await ousdMint.getAccountInfo(
+      treasuryOusdToken
+    );
+    assert.ok(treasuryOusdTokenAccount.amount.eq(earlyExitFee));
+    let initializerOusdTokenAccount = await ousdMint.getAccountInfo(
+      initializerOusdToken
+    );
+    assert.ok(
+      initializerOusdTokenAccount.amount.eq(
+        DEPOSIT_AMOUNT.muln(1_000).sub(earlyExitFee)
+      )
+    );
+
+    // reset cooldown & fee, return the fee for the following tests
+    await updateWithdrawCooldown(0, 0);
+    await ousdMint.transfer(
+      treasuryOusdToken,
+      initializerOusdToken,
+      ADMIN_KEYPAIR,
+      [],
+      earlyExitFee.toNumber()
+    );
+    initializerOusdTokenAccount = await ousdMint.getAccountInfo(
+      initializerOusdToken
+    );
+    assert.ok(initializerOusdTokenAccount.amount.eq(DEPOSIT_AMOUNT.muln(1_000)));
+  });
+
   it("should redeem 100 $1USD", async () => {
     try {
       await program.rpc.redeem(
         {
           amount: DEPOSIT_AMOUNT.muln(1_000),
+          minAmountOut: new BN("0"),
+          expiry: null,
         },
         {
           accounts: {
@@ -387,8 +908,9 @@ describe("onering-finance", () => {
             initializerStableToken,
             ousdMint: ousdMint.publicKey,
             initializerOusdToken,
-            market: MARKET_KEYPAIR.publicKey,
-            state: STATE_KEYPAIR.publicKey,
+            treasuryOusdToken,
+            market: marketPda,
+            state: statePda,
             tokenProgram: TOKEN_PROGRAM_ID,
           },
           signers: [USER_KEYPAIR],
@@ -403,6 +925,8 @@ describe("onering-finance", () => {
     await program.rpc.redeem(
       {
         amount: DEPOSIT_AMOUNT.muln(1_000),
+        minAmountOut: new BN("0"),
+        expiry: null,
       },
       {
         accounts: {
@@ -413,8 +937,9 @@ describe("onering-finance", () => {
           initializerStableToken,
           ousdMint: ousdMint.publicKey,
           initializerOusdToken,
-          market: MARKET_KEYPAIR.publicKey,
-          state: STATE_KEYPAIR.publicKey,
+          treasuryOusdToken,
+          market: marketPda,
+          state: statePda,
           tokenProgram: TOKEN_PROGRAM_ID,
         },
         signers: [USER_KEYPAIR],
@@ -438,6 +963,8 @@ describe("onering-finance", () => {
     await program.rpc.mintAndDeposit(
       {
         amount: DEPOSIT_AMOUNT,
+        minAmountOut: new BN("0"),
+        expiry: null,
       },
       {
         accounts: {
@@ -446,9 +973,11 @@ describe("onering-finance", () => {
           stableVault: stableVaultPda,
           initializerStableToken,
           ousdMint: ousdMint.publicKey,
+          ousdMintAuth: ousdMintAuthPda,
+          treasuryOusdToken,
           reserve: reservePda,
-          market: MARKET_KEYPAIR.publicKey,
-          state: STATE_KEYPAIR.publicKey,
+          market: marketPda,
+          state: statePda,
           tokenProgram: TOKEN_PROGRAM_ID,
         },
         signers: [USER_KEYPAIR],
@@ -460,7 +989,7 @@ describe("onering-finance", () => {
     assert.ok(reserve.nonce === reserveBump);
     assert.ok(reserve.depositAmount.eq(DEPOSIT_AMOUNT.muln(1_000)));
     assert.ok(!reserve.freezeFlag);
-    const state = await program.account.state.fetch(STATE_KEYPAIR.publicKey);
+    const state = await program.account.state.fetch(statePda);
     assert.ok(state.depositAmount.eq(DEPOSIT_AMOUNT.muln(1_000)));
     const stableVaultAccount = await stableMint.getAccountInfo(stableVaultPda);
     assert.ok(stableVaultAccount.amount.eq(DEPOSIT_AMOUNT));
@@ -473,4 +1002,1490 @@ describe("onering-finance", () => {
     );
     assert.ok(initializerOusdTokenAccount.amount.eq(new BN("0")));
   });
+
+  it("should distribute 1 $1USD of harvested yield", async () => {
+    await stableMint.mintTo(
+      adminStableToken,
+      STABLE_MINT_AUTH_KEYPAIR.publicKey,
+      [STABLE_MINT_AUTH_KEYPAIR],
+      HARVEST_AMOUNT.toNumber()
+    );
+
+    const distributeRewardsAccounts = {
+      accounts: {
+        admin: ADMIN_KEYPAIR.publicKey,
+        stableMint: stableMint.publicKey,
+        stableVault: stableVaultPda,
+        market: marketPda,
+        stableVaultAuth: stableVaultAuthPda,
+        adminStableToken,
+        ousdMint: ousdMint.publicKey,
+        state: statePda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+      remainingAccounts: [
+        { pubkey: marketPda, isWritable: false, isSigner: false },
+        { pubkey: stableVaultPda, isWritable: false, isSigner: false },
+        { pubkey: stableMint.publicKey, isWritable: false, isSigner: false },
+      ],
+      signers: [ADMIN_KEYPAIR],
+    };
+
+    // token account of a worthless mint owned by the vault authority
+    const fakeMint = await Token.createMint(
+      provider.connection,
+      FEE_PAYER_KEYPAIR,
+      FEE_PAYER_KEYPAIR.publicKey,
+      null,
+      6,
+      TOKEN_PROGRAM_ID
+    );
+    const fakeVault = await fakeMint.createAccount(stableVaultAuthPda);
+    await fakeMint.mintTo(
+      fakeVault,
+      FEE_PAYER_KEYPAIR.publicKey,
+      [],
+      HARVEST_AMOUNT.toNumber()
+    );
+    try {
+      await program.rpc.distributeRewards(
+        {
+          harvestAmount: new BN("0"),
+          amount: HARVEST_AMOUNT.muln(1_000),
+        },
+        {
+          ...distributeRewardsAccounts,
+          remainingAccounts: [
+            ...distributeRewardsAccounts.remainingAccounts,
+            { pubkey: marketPda, isWritable: false, isSigner: false },
+            { pubkey: fakeVault, isWritable: false, isSigner: false },
+            { pubkey: fakeMint.publicKey, isWritable: false, isSigner: false },
+          ],
+        }
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Stable vault is invalid");
+    }
+
+    // vault assets only cover deposits before harvest
+    try {
+      await program.rpc.distributeRewards(
+        {
+          harvestAmount: new BN("0"),
+          amount: HARVEST_AMOUNT.muln(1_000),
+        },
+        distributeRewardsAccounts
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Reward amount exceeds surplus of vault assets");
+    }
+
+    await program.rpc.distributeRewards(
+      {
+        harvestAmount: HARVEST_AMOUNT,
+        amount: HARVEST_AMOUNT.muln(1_000),
+      },
+      distributeRewardsAccounts
+    );
+
+    // asserts
+    const state = await program.account.state.fetch(statePda);
+    assert.ok(state.rewardAmount.eq(HARVEST_AMOUNT.muln(1_000)));
+    assert.ok(state.lastUpdateTime.gtn(0));
+    const stableVaultAccount = await stableMint.getAccountInfo(stableVaultPda);
+    assert.ok(stableVaultAccount.amount.eq(DEPOSIT_AMOUNT.add(HARVEST_AMOUNT)));
+
+    // settle rewards into the reserve, nothing claimed yet
+    await program.rpc.claim(
+      { amount: new BN("0") },
+      {
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          ousdMint: ousdMint.publicKey,
+          ousdMintAuth: ousdMintAuthPda,
+          initializerOusdToken,
+          reserve: reservePda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [USER_KEYPAIR],
+      }
+    );
+    const refreshedState = await program.account.state.fetch(statePda);
+    assert.ok(
+      refreshedState.rewardAmount
+        .add(refreshedState.unclaimedRewardAmount)
+        .eq(HARVEST_AMOUNT.muln(1_000))
+    );
+
+    // settled rewards are still owed, harvest is not distributable twice
+    try {
+      await program.rpc.distributeRewards(
+        {
+          harvestAmount: new BN("0"),
+          amount: HARVEST_AMOUNT.muln(1_000),
+        },
+        distributeRewardsAccounts
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Reward amount exceeds surplus of vault assets");
+    }
+  });
+
+  it("should queue, cancel, fulfill & escrow redemptions of 100 $1USD", async () => {
+    // withdraw deposits to redeem
+    await program.rpc.withdraw(
+      {
+        amount: DEPOSIT_AMOUNT.muln(1_000),
+      },
+      {
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          ousdMint: ousdMint.publicKey,
+          ousdMintAuth: ousdMintAuthPda,
+          initializerOusdToken,
+          treasuryOusdToken,
+          reserve: reservePda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [USER_KEYPAIR],
+      }
+    );
+
+    // no withdrawal liquidity left
+    try {
+      await program.rpc.redeem(
+        {
+          amount: DEPOSIT_AMOUNT.muln(1_000),
+          minAmountOut: new BN("0"),
+          expiry: null,
+        },
+        {
+          accounts: {
+            initializer: USER_KEYPAIR.publicKey,
+            stableMint: stableMint.publicKey,
+            stableVault: stableVaultPda,
+            stableVaultAuth: stableVaultAuthPda,
+            initializerStableToken,
+            ousdMint: ousdMint.publicKey,
+            initializerOusdToken,
+            treasuryOusdToken,
+            market: marketPda,
+            state: statePda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          },
+          signers: [USER_KEYPAIR],
+        }
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Insufficient withdrawal liquidity");
+    }
+
+    const queueRedemption = (amount: BN) =>
+      program.rpc.queueRedemption(
+        {
+          nonce: ticketBump,
+          amount,
+        },
+        {
+          accounts: {
+            initializer: USER_KEYPAIR.publicKey,
+            ousdMint: ousdMint.publicKey,
+            initializerOusdToken,
+            ticket: ticketPda,
+            market: marketPda,
+            state: statePda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [USER_KEYPAIR],
+        }
+      );
+
+    const fulfillRedemption = (ticket = ticketPda) =>
+      program.rpc.fulfillRedemption({
+        accounts: {
+          initializer: FEE_PAYER_KEYPAIR.publicKey,
+          owner: USER_KEYPAIR.publicKey,
+          stableMint: stableMint.publicKey,
+          stableVault: stableVaultPda,
+          stableVaultAuth: stableVaultAuthPda,
+          ownerStableToken: initializerStableToken,
+          ousdMint: ousdMint.publicKey,
+          ousdMintAuth: ousdMintAuthPda,
+          treasuryOusdToken,
+          ticket,
+          market: marketPda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [FEE_PAYER_KEYPAIR],
+      });
+
+    const escrowRedemption = (initializer: Keypair) =>
+      program.rpc.escrowRedemption({
+        accounts: {
+          initializer: initializer.publicKey,
+          ousdMint: ousdMint.publicKey,
+          ousdMintAuth: ousdMintAuthPda,
+          treasuryOusdToken,
+          ticket: ticketPda,
+          market: marketPda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [initializer],
+      });
+
+    const addWithdrawalLiq = (amount: BN) =>
+      program.rpc.updateMarket(
+        {
+          lockFlag: false,
+          withdrawalLiq: amount,
+          incrementWithdrawalLiq: true,
+        },
+        {
+          accounts: {
+            admin: ADMIN_KEYPAIR.publicKey,
+            market: marketPda,
+            state: statePda,
+          },
+          signers: [ADMIN_KEYPAIR],
+        }
+      );
+
+    // nothing to queue
+    [ticketPda, ticketBump] = await findTicketPda(
+      USER_KEYPAIR.publicKey,
+      marketPda,
+      0
+    );
+    try {
+      await queueRedemption(new BN("0"));
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Amount must be greater than zero");
+    }
+
+    // queue
+    await queueRedemption(DEPOSIT_AMOUNT.muln(1_000));
+    let ticket = await program.account.redeemTicket.fetch(ticketPda);
+    assert.ok(ticket.owner.equals(USER_KEYPAIR.publicKey));
+    assert.ok(ticket.queueId.eqn(0));
+    assert.ok(ticket.ousdAmount.eq(DEPOSIT_AMOUNT.muln(1_000)));
+    assert.equal(ticket.feeBps, 0);
+    let market = await program.account.market.fetch(marketPda);
+    assert.ok(market.queueTail.eqn(1));
+    assert.ok(market.queuedAmount.eq(DEPOSIT_AMOUNT.muln(1_000)));
+    let initializerOusdTokenAccount = await ousdMint.getAccountInfo(
+      initializerOusdToken
+    );
+    assert.ok(initializerOusdTokenAccount.amount.eqn(0));
+
+    try {
+      await fulfillRedemption();
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Insufficient withdrawal liquidity");
+    }
+
+    // cancel, the cancelled ticket is popped later
+    await program.rpc.cancelRedemption({
+      accounts: {
+        initializer: USER_KEYPAIR.publicKey,
+        ousdMint: ousdMint.publicKey,
+        ousdMintAuth: ousdMintAuthPda,
+        initializerOusdToken,
+        ticket: ticketPda,
+        market: marketPda,
+        state: statePda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+      signers: [USER_KEYPAIR],
+    });
+    ticket = await program.account.redeemTicket.fetch(ticketPda);
+    assert.ok(ticket.ousdAmount.eqn(0));
+    initializerOusdTokenAccount = await ousdMint.getAccountInfo(
+      initializerOusdToken
+    );
+    assert.ok(initializerOusdTokenAccount.amount.eq(DEPOSIT_AMOUNT.muln(1_000)));
+
+    // queue half again while the cancelled ticket is still open
+    const cancelledTicketPda = ticketPda;
+    [ticketPda, ticketBump] = await findTicketPda(
+      USER_KEYPAIR.publicKey,
+      marketPda,
+      1
+    );
+    await queueRedemption(DEPOSIT_AMOUNT.muln(500));
+    ticket = await program.account.redeemTicket.fetch(ticketPda);
+    assert.ok(ticket.queueId.eqn(1));
+
+    // pop the cancelled ticket, then fulfill once liquidity arrives
+    await fulfillRedemption(cancelledTicketPda);
+    market = await program.account.market.fetch(marketPda);
+    assert.ok(market.queueHead.eqn(1));
+    assert.ok(market.queuedAmount.eq(DEPOSIT_AMOUNT.muln(500)));
+    assert.equal(
+      await provider.connection.getAccountInfo(cancelledTicketPda),
+      null
+    );
+
+    await addWithdrawalLiq(DEPOSIT_AMOUNT.muln(500));
+    await fulfillRedemption();
+    assert.equal(await provider.connection.getAccountInfo(ticketPda), null);
+
+    // liquidity covers the amount, redeem instead of queueing
+    [ticketPda, ticketBump] = await findTicketPda(
+      USER_KEYPAIR.publicKey,
+      marketPda,
+      2
+    );
+    await addWithdrawalLiq(DEPOSIT_AMOUNT.muln(250));
+    try {
+      await queueRedemption(DEPOSIT_AMOUNT.muln(250));
+      assert.fail();
+    } catch (err) {
+      assert.equal(
+        err.msg,
+        "Withdrawal liquidity is sufficient, redeem instead"
+      );
+    }
+
+    // queue the other half, escrowed since the owner may not be able to receive
+    await queueRedemption(DEPOSIT_AMOUNT.muln(500));
+    const { events } = await program.simulate.getQueuePosition({
+      accounts: {
+        ticket: ticketPda,
+        market: marketPda,
+      },
+    });
+    assert.equal(events[0].name, "QueuePositionEvent");
+    assert.ok(events[0].data.queueId.eqn(2));
+    assert.ok(events[0].data.queuePosition.eqn(0));
+
+    await addWithdrawalLiq(DEPOSIT_AMOUNT.muln(250));
+    try {
+      await escrowRedemption(FEE_PAYER_KEYPAIR);
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Access denied");
+    }
+    await escrowRedemption(ADMIN_KEYPAIR);
+
+    ticket = await program.account.redeemTicket.fetch(ticketPda);
+    assert.ok(ticket.ousdAmount.eqn(0));
+    assert.ok(ticket.escrowedAmount.eq(DEPOSIT_AMOUNT.muln(500)));
+    market = await program.account.market.fetch(marketPda);
+    assert.ok(market.queueHead.eqn(3));
+    assert.ok(market.escrowedAmount.eq(DEPOSIT_AMOUNT.muln(500)));
+    let state = await program.account.state.fetch(statePda);
+    assert.ok(state.escrowedRedeemAmount.eq(DEPOSIT_AMOUNT.muln(500)));
+
+    // escrowed ticket cannot be cancelled, only claimed by its owner
+    try {
+      await program.rpc.cancelRedemption({
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          ousdMint: ousdMint.publicKey,
+          ousdMintAuth: ousdMintAuthPda,
+          initializerOusdToken,
+          ticket: ticketPda,
+          market: marketPda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [USER_KEYPAIR],
+      });
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Redemption ticket already cancelled");
+    }
+
+    await program.rpc.claimRedemption({
+      accounts: {
+        initializer: USER_KEYPAIR.publicKey,
+        stableMint: stableMint.publicKey,
+        stableVault: stableVaultPda,
+        stableVaultAuth: stableVaultAuthPda,
+        recipientStableToken: initializerStableToken,
+        ousdMint: ousdMint.publicKey,
+        ticket: ticketPda,
+        market: marketPda,
+        state: statePda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+      signers: [USER_KEYPAIR],
+    });
+
+    // asserts
+    market = await program.account.market.fetch(marketPda);
+    assert.ok(market.queueHead.eqn(3));
+    assert.ok(market.withdrawalLiq.eqn(0));
+    assert.ok(market.queuedAmount.eqn(0));
+    assert.ok(market.escrowedAmount.eqn(0));
+    state = await program.account.state.fetch(statePda);
+    assert.ok(state.queuedRedeemAmount.eqn(0));
+    assert.ok(state.escrowedRedeemAmount.eqn(0));
+    const initializerStableTokenAccount = await stableMint.getAccountInfo(
+      initializerStableToken
+    );
+    assert.ok(initializerStableTokenAccount.amount.eq(DEPOSIT_AMOUNT));
+    assert.equal(await provider.connection.getAccountInfo(ticketPda), null);
+  });
+
+  it("should charge mint & redeem fees", async () => {
+    await program.rpc.updateFees(
+      {
+        mintFeeBps: FEE_BPS,
+        redeemFeeBps: FEE_BPS,
+        swapFeeBps: 0,
+      },
+      {
+        accounts: {
+          admin: ADMIN_KEYPAIR.publicKey,
+          market: marketPda,
+          state: statePda,
+        },
+        signers: [ADMIN_KEYPAIR],
+      }
+    );
+
+    const ousdAmount = DEPOSIT_AMOUNT.muln(1_000);
+    const mintFee = ousdAmount.muln(FEE_BPS).divn(10_000);
+    await program.rpc.mintOusd(
+      {
+        amount: DEPOSIT_AMOUNT,
+        minAmountOut: new BN("0"),
+        expiry: null,
+      },
+      {
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          stableMint: stableMint.publicKey,
+          stableVault: stableVaultPda,
+          initializerStableToken,
+          ousdMint: ousdMint.publicKey,
+          ousdMintAuth: ousdMintAuthPda,
+          initializerOusdToken,
+          treasuryOusdToken,
+          market: marketPda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [USER_KEYPAIR],
+      }
+    );
+
+    // asserts
+    let treasuryOusdTokenAccount = await ousdMint.getAccountInfo(
+      treasuryOusdToken
+    );
+    assert.ok(treasuryOusdTokenAccount.amount.eq(mintFee));
+    let initializerOusdTokenAccount = await ousdMint.getAccountInfo(
+      initializerOusdToken
+    );
+    assert.ok(initializerOusdTokenAccount.amount.eq(ousdAmount.sub(mintFee)));
+
+    // redeem all minted 1USD
+    const redeemAmount = ousdAmount.sub(mintFee);
+    const redeemFee = redeemAmount.muln(FEE_BPS).addn(9_999).divn(10_000);
+    await program.rpc.updateMarket(
+      {
+        lockFlag: false,
+        withdrawalLiq: redeemAmount,
+        incrementWithdrawalLiq: false,
+      },
+      {
+        accounts: {
+          admin: ADMIN_KEYPAIR.publicKey,
+          market: marketPda,
+          state: statePda,
+        },
+        signers: [ADMIN_KEYPAIR],
+      }
+    );
+    await program.rpc.redeem(
+      {
+        amount: redeemAmount,
+        minAmountOut: new BN("0"),
+        expiry: null,
+      },
+      {
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          stableMint: stableMint.publicKey,
+          stableVault: stableVaultPda,
+          stableVaultAuth: stableVaultAuthPda,
+          initializerStableToken,
+          ousdMint: ousdMint.publicKey,
+          initializerOusdToken,
+          treasuryOusdToken,
+          market: marketPda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [USER_KEYPAIR],
+      }
+    );
+
+    // asserts
+    treasuryOusdTokenAccount = await ousdMint.getAccountInfo(treasuryOusdToken);
+    assert.ok(treasuryOusdTokenAccount.amount.eq(mintFee.add(redeemFee)));
+    initializerOusdTokenAccount = await ousdMint.getAccountInfo(
+      initializerOusdToken
+    );
+    assert.ok(initializerOusdTokenAccount.amount.eqn(0));
+    const initializerStableTokenAccount = await stableMint.getAccountInfo(
+      initializerStableToken
+    );
+    assert.ok(
+      initializerStableTokenAccount.amount.eq(
+        redeemAmount.sub(redeemFee).divn(1_000)
+      )
+    );
+  });
+
+  it("should swap 10 stable tokens between markets", async () => {
+    // market of another stable token, its vault holds swap liquidity
+    const swapStableMint = await Token.createMint(
+      provider.connection,
+      FEE_PAYER_KEYPAIR,
+      STABLE_MINT_AUTH_KEYPAIR.publicKey,
+      null,
+      6,
+      TOKEN_PROGRAM_ID
+    );
+    const [swapMarketPda, swapMarketBump] = await PublicKey.findProgramAddress(
+      [
+        statePda.toBuffer(),
+        Buffer.from(anchor.utils.bytes.utf8.encode(MARKET_SEED)),
+        swapStableMint.publicKey.toBuffer(),
+      ],
+      program.programId
+    );
+    const [swapStableVaultPda, swapStableVaultBump] =
+      await PublicKey.findProgramAddress(
+        [
+          swapStableMint.publicKey.toBuffer(),
+          Buffer.from(anchor.utils.bytes.utf8.encode(STABLE_VAULT_SEED)),
+          swapMarketPda.toBuffer(),
+        ],
+        program.programId
+      );
+    await program.rpc.createMarket(
+      {
+        marketBump: swapMarketBump,
+        stableVaultBump: swapStableVaultBump,
+      },
+      {
+        accounts: {
+          admin: ADMIN_KEYPAIR.publicKey,
+          stableMint: swapStableMint.publicKey,
+          stableVault: swapStableVaultPda,
+          stableVaultAuth: stableVaultAuthPda,
+          market: swapMarketPda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        },
+        signers: [ADMIN_KEYPAIR],
+      }
+    );
+    await program.rpc.updateFees(
+      {
+        mintFeeBps: 0,
+        redeemFeeBps: 0,
+        swapFeeBps: SWAP_FEE_BPS,
+      },
+      {
+        accounts: {
+          admin: ADMIN_KEYPAIR.publicKey,
+          market: swapMarketPda,
+          state: statePda,
+        },
+        signers: [ADMIN_KEYPAIR],
+      }
+    );
+    await swapStableMint.mintTo(
+      swapStableVaultPda,
+      STABLE_MINT_AUTH_KEYPAIR.publicKey,
+      [STABLE_MINT_AUTH_KEYPAIR],
+      DEPOSIT_AMOUNT.toNumber()
+    );
+
+    const swapStableToken = await swapStableMint.createAssociatedTokenAccount(
+      USER_KEYPAIR.publicKey
+    );
+    await stableMint.mintTo(
+      initializerStableToken,
+      STABLE_MINT_AUTH_KEYPAIR.publicKey,
+      [STABLE_MINT_AUTH_KEYPAIR],
+      DEPOSIT_AMOUNT.toNumber()
+    );
+
+    const updateSwapLiq = (withdrawalLiq: BN) =>
+      program.rpc.updateMarket(
+        {
+          lockFlag: false,
+          withdrawalLiq,
+          incrementWithdrawalLiq: false,
+        },
+        {
+          accounts: {
+            admin: ADMIN_KEYPAIR.publicKey,
+            market: swapMarketPda,
+            state: statePda,
+          },
+          signers: [ADMIN_KEYPAIR],
+        }
+      );
+
+    const swapStable = (minAmountOut: BN, marketOut: PublicKey) =>
+      program.rpc.swapStable(
+        {
+          amount: SWAP_AMOUNT,
+          minAmountOut,
+          expiry: null,
+        },
+        {
+          accounts: {
+            initializer: USER_KEYPAIR.publicKey,
+            stableMintIn: stableMint.publicKey,
+            stableVaultIn: stableVaultPda,
+            initializerStableTokenIn: initializerStableToken,
+            marketIn: marketPda,
+            stableMintOut: marketOut.equals(marketPda)
+              ? stableMint.publicKey
+              : swapStableMint.publicKey,
+            stableVaultOut: marketOut.equals(marketPda)
+              ? stableVaultPda
+              : swapStableVaultPda,
+            initializerStableTokenOut: marketOut.equals(marketPda)
+              ? initializerStableToken
+              : swapStableToken,
+            marketOut,
+            stableVaultAuth: stableVaultAuthPda,
+            ousdMint: ousdMint.publicKey,
+            state: statePda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          },
+          signers: [USER_KEYPAIR],
+        }
+      );
+
+    // same stable token on both sides
+    try {
+      await swapStable(new BN("0"), marketPda);
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Cannot swap a stable token for itself");
+    }
+
+    // queued redemptions of the output market are served first
+    await program.rpc.mintOusd(
+      {
+        amount: SWAP_AMOUNT,
+        minAmountOut: new BN("0"),
+        expiry: null,
+      },
+      {
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          stableMint: stableMint.publicKey,
+          stableVault: stableVaultPda,
+          initializerStableToken,
+          ousdMint: ousdMint.publicKey,
+          ousdMintAuth: ousdMintAuthPda,
+          initializerOusdToken,
+          treasuryOusdToken,
+          market: marketPda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [USER_KEYPAIR],
+      }
+    );
+    const queuedAmount = (
+      await ousdMint.getAccountInfo(initializerOusdToken)
+    ).amount;
+    const [swapTicketPda, swapTicketBump] = await findTicketPda(
+      USER_KEYPAIR.publicKey,
+      swapMarketPda,
+      0
+    );
+    await program.rpc.queueRedemption(
+      {
+        nonce: swapTicketBump,
+        amount: queuedAmount,
+      },
+      {
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          ousdMint: ousdMint.publicKey,
+          initializerOusdToken,
+          ticket: swapTicketPda,
+          market: swapMarketPda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        },
+        signers: [USER_KEYPAIR],
+      }
+    );
+
+    const ousdAmount = SWAP_AMOUNT.muln(1_000);
+    const swapFee = ousdAmount.muln(SWAP_FEE_BPS).addn(9_999).divn(10_000);
+    const stableAmountOut = ousdAmount.sub(swapFee).divn(1_000);
+    await updateSwapLiq(ousdAmount);
+    try {
+      await swapStable(new BN("0"), swapMarketPda);
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Insufficient withdrawal liquidity");
+    }
+
+    await program.rpc.fulfillRedemption({
+      accounts: {
+        initializer: FEE_PAYER_KEYPAIR.publicKey,
+        owner: USER_KEYPAIR.publicKey,
+        stableMint: swapStableMint.publicKey,
+        stableVault: swapStableVaultPda,
+        stableVaultAuth: stableVaultAuthPda,
+        ownerStableToken: swapStableToken,
+        ousdMint: ousdMint.publicKey,
+        ousdMintAuth: ousdMintAuthPda,
+        treasuryOusdToken,
+        ticket: swapTicketPda,
+        market: swapMarketPda,
+        state: statePda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+      signers: [FEE_PAYER_KEYPAIR],
+    });
+    const redeemedAmount = (
+      await swapStableMint.getAccountInfo(swapStableToken)
+    ).amount;
+    assert.ok(redeemedAmount.eq(queuedAmount.divn(1_000)));
+
+    // swap fee is charged by the output market, slippage counts it
+    await updateSwapLiq(ousdAmount);
+    try {
+      await swapStable(SWAP_AMOUNT, swapMarketPda);
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Slippage exceeded");
+    }
+
+    const market = await program.account.market.fetch(marketPda);
+    await swapStable(stableAmountOut, swapMarketPda);
+
+    // asserts
+    const swapStableTokenAccount = await swapStableMint.getAccountInfo(
+      swapStableToken
+    );
+    assert.ok(
+      swapStableTokenAccount.amount.eq(redeemedAmount.add(stableAmountOut))
+    );
+    const swapMarket = await program.account.market.fetch(swapMarketPda);
+    assert.ok(swapMarket.withdrawalLiq.eq(swapFee));
+    const marketAfter = await program.account.market.fetch(marketPda);
+    assert.ok(
+      marketAfter.outstandingAmount.eq(
+        market.outstandingAmount.add(ousdAmount.sub(swapFee))
+      )
+    );
+    const initializerOusdTokenAccount = await ousdMint.getAccountInfo(
+      initializerOusdToken
+    );
+    assert.ok(initializerOusdTokenAccount.amount.eqn(0));
+  });
+
+  it("should flash mint & repay 100 $1USD in one transaction", async () => {
+    await program.rpc.updateFlashMint(
+      {
+        flashMintCap: DEPOSIT_AMOUNT.muln(1_000),
+        flashFeeBps: 0,
+      },
+      {
+        accounts: {
+          admin: ADMIN_KEYPAIR.publicKey,
+          state: statePda,
+        },
+        signers: [ADMIN_KEYPAIR],
+      }
+    );
+
+    const flashMint = (amount: BN) =>
+      program.instruction.flashMint(
+        { amount },
+        {
+          accounts: {
+            initializer: USER_KEYPAIR.publicKey,
+            receiverOusdToken: initializerOusdToken,
+            ousdMint: ousdMint.publicKey,
+            ousdMintAuth: ousdMintAuthPda,
+            state: statePda,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          },
+        }
+      );
+
+    // flash mint must be repaid in the same transaction
+    try {
+      await program.rpc.flashMint(
+        { amount: DEPOSIT_AMOUNT.muln(1_000) },
+        {
+          accounts: {
+            initializer: USER_KEYPAIR.publicKey,
+            receiverOusdToken: initializerOusdToken,
+            ousdMint: ousdMint.publicKey,
+            ousdMintAuth: ousdMintAuthPda,
+            state: statePda,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          },
+          signers: [USER_KEYPAIR],
+        }
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Flash mint is not repaid in the same transaction");
+    }
+
+    // flash mint cap
+    try {
+      await program.rpc.flashRepay(
+        { flashMintIndex: 0 },
+        {
+          accounts: {
+            initializer: USER_KEYPAIR.publicKey,
+            initializerOusdToken,
+            treasuryOusdToken,
+            ousdMint: ousdMint.publicKey,
+            state: statePda,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          },
+          instructions: [flashMint(DEPOSIT_AMOUNT.muln(1_000).addn(1))],
+          signers: [USER_KEYPAIR],
+        }
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Flash mint amount exceeds the cap");
+    }
+
+    const ousdSupply = (await ousdMint.getMintInfo()).supply;
+    await program.rpc.flashRepay(
+      { flashMintIndex: 0 },
+      {
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          initializerOusdToken,
+          treasuryOusdToken,
+          ousdMint: ousdMint.publicKey,
+          state: statePda,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        instructions: [flashMint(DEPOSIT_AMOUNT.muln(1_000))],
+        signers: [USER_KEYPAIR],
+      }
+    );
+
+    // asserts
+    assert.ok((await ousdMint.getMintInfo()).supply.eq(ousdSupply));
+    const initializerOusdTokenAccount = await ousdMint.getAccountInfo(
+      initializerOusdToken
+    );
+    assert.ok(initializerOusdTokenAccount.amount.eqn(0));
+  });
+
+  it("should enable share token (s1USD) mode", async () => {
+    const shareMint = await Token.createMint(
+      provider.connection,
+      FEE_PAYER_KEYPAIR,
+      ousdMintAuthPda,
+      null,
+      9,
+      TOKEN_PROGRAM_ID
+    );
+    const enableShareMode = () =>
+      program.rpc.enableShareMode({
+        accounts: {
+          admin: ADMIN_KEYPAIR.publicKey,
+          shareMint: shareMint.publicKey,
+          ousdMint: ousdMint.publicKey,
+          ousdMintAuth: ousdMintAuthPda,
+          state: statePda,
+        },
+        signers: [ADMIN_KEYPAIR],
+      });
+
+    await enableShareMode();
+    try {
+      await enableShareMode();
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Share mode is already enabled");
+    }
+
+    // asserts
+    const state = await program.account.state.fetch(statePda);
+    assert.ok(state.shareMint.equals(shareMint.publicKey));
+    assert.ok(state.totalShares.eqn(0));
+    assert.ok(state.totalUnderlying.eqn(0));
+  });
+
+  it("should compound rewards by the reserve delegate only", async () => {
+    await program.rpc.setDelegate(
+      { delegate: NEW_ADMIN_KEYPAIR.publicKey },
+      {
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          reserve: reservePda,
+          state: statePda,
+        },
+        signers: [USER_KEYPAIR],
+      }
+    );
+
+    let reserve = await program.account.reserve.fetch(reservePda);
+    assert.ok(reserve.delegate.equals(NEW_ADMIN_KEYPAIR.publicKey));
+
+    const claimAndDepositAccounts = {
+      accounts: {
+        initializer: NEW_ADMIN_KEYPAIR.publicKey,
+        owner: USER_KEYPAIR.publicKey,
+        reserve: reservePda,
+        state: statePda,
+      },
+      signers: [NEW_ADMIN_KEYPAIR],
+    };
+    await program.rpc.claimAndDeposit(
+      { amount: reserve.rewardAmount },
+      claimAndDepositAccounts
+    );
+
+    await program.rpc.revokeDelegate({
+      accounts: {
+        initializer: USER_KEYPAIR.publicKey,
+        reserve: reservePda,
+        state: statePda,
+      },
+      signers: [USER_KEYPAIR],
+    });
+
+    reserve = await program.account.reserve.fetch(reservePda);
+    assert.ok(reserve.delegate.equals(PublicKey.default));
+
+    try {
+      await program.rpc.claimAndDeposit(
+        { amount: new BN("0") },
+        claimAndDepositAccounts
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Access denied");
+    }
+  });
+
+  it("should close an empty reserve & market", async () => {
+    // reserve of another owner
+    const [ownerReservePda, ownerReserveBump] =
+      await PublicKey.findProgramAddress(
+        [
+          NEW_ADMIN_KEYPAIR.publicKey.toBuffer(),
+          Buffer.from(anchor.utils.bytes.utf8.encode(RESERVE_SEED)),
+          statePda.toBuffer(),
+        ],
+        program.programId
+      );
+    await program.rpc.createReserve(
+      { nonce: ownerReserveBump },
+      {
+        accounts: {
+          initializer: NEW_ADMIN_KEYPAIR.publicKey,
+          reserve: ownerReservePda,
+          state: statePda,
+          systemProgram: SystemProgram.programId,
+        },
+        signers: [NEW_ADMIN_KEYPAIR],
+      }
+    );
+    await program.rpc.closeReserve({
+      accounts: {
+        initializer: NEW_ADMIN_KEYPAIR.publicKey,
+        reserve: ownerReservePda,
+        state: statePda,
+      },
+      signers: [NEW_ADMIN_KEYPAIR],
+    });
+    assert.equal(
+      await provider.connection.getAccountInfo(ownerReservePda),
+      null
+    );
+
+    // market of another stable token, nothing minted against it
+    const otherStableMint = await Token.createMint(
+      provider.connection,
+      FEE_PAYER_KEYPAIR,
+      STABLE_MINT_AUTH_KEYPAIR.publicKey,
+      null,
+      6,
+      TOKEN_PROGRAM_ID
+    );
+    const [otherMarketPda, otherMarketBump] =
+      await PublicKey.findProgramAddress(
+        [
+          statePda.toBuffer(),
+          Buffer.from(anchor.utils.bytes.utf8.encode(MARKET_SEED)),
+          otherStableMint.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+    const [otherStableVaultPda, otherStableVaultBump] =
+      await PublicKey.findProgramAddress(
+        [
+          otherStableMint.publicKey.toBuffer(),
+          Buffer.from(anchor.utils.bytes.utf8.encode(STABLE_VAULT_SEED)),
+          otherMarketPda.toBuffer(),
+        ],
+        program.programId
+      );
+    await program.rpc.createMarket(
+      {
+        marketBump: otherMarketBump,
+        stableVaultBump: otherStableVaultBump,
+      },
+      {
+        accounts: {
+          admin: ADMIN_KEYPAIR.publicKey,
+          stableMint: otherStableMint.publicKey,
+          stableVault: otherStableVaultPda,
+          stableVaultAuth: stableVaultAuthPda,
+          market: otherMarketPda,
+          state: statePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+        },
+        signers: [ADMIN_KEYPAIR],
+      }
+    );
+
+    // market with 1USD minted against it stays open
+    try {
+      await program.rpc.closeMarket({
+        accounts: {
+          admin: ADMIN_KEYPAIR.publicKey,
+          stableVault: stableVaultPda,
+          stableVaultAuth: stableVaultAuthPda,
+          market: marketPda,
+          state: statePda,
+          treasuryOusdToken,
+          treasuryStableToken: adminStableToken,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+        signers: [ADMIN_KEYPAIR],
+      });
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Market is not empty");
+    }
+
+    // dust sent to the vault is swept to treasury on close
+    const otherStableToken = await otherStableMint.createAssociatedTokenAccount(
+      USER_KEYPAIR.publicKey
+    );
+    await otherStableMint.mintTo(
+      otherStableToken,
+      STABLE_MINT_AUTH_KEYPAIR.publicKey,
+      [STABLE_MINT_AUTH_KEYPAIR],
+      1
+    );
+    await otherStableMint.transfer(
+      otherStableToken,
+      otherStableVaultPda,
+      USER_KEYPAIR.publicKey,
+      [USER_KEYPAIR],
+      1
+    );
+    const otherTreasuryStableToken =
+      await otherStableMint.createAssociatedTokenAccount(
+        ADMIN_KEYPAIR.publicKey
+      );
+
+    await program.rpc.closeMarket({
+      accounts: {
+        admin: ADMIN_KEYPAIR.publicKey,
+        stableVault: otherStableVaultPda,
+        stableVaultAuth: stableVaultAuthPda,
+        market: otherMarketPda,
+        state: statePda,
+        treasuryOusdToken,
+        treasuryStableToken: otherTreasuryStableToken,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      },
+      signers: [ADMIN_KEYPAIR],
+    });
+    const otherTreasuryStableTokenAccount =
+      await otherStableMint.getAccountInfo(otherTreasuryStableToken);
+    assert.ok(otherTreasuryStableTokenAccount.amount.eqn(1));
+    assert.equal(await provider.connection.getAccountInfo(otherMarketPda), null);
+    assert.equal(
+      await provider.connection.getAccountInfo(otherStableVaultPda),
+      null
+    );
+  });
+
+  it("should transfer a reserve to another wallet and back", async () => {
+    const [recipientReservePda, recipientReserveBump] =
+      await PublicKey.findProgramAddress(
+        [
+          NEW_ADMIN_KEYPAIR.publicKey.toBuffer(),
+          Buffer.from(anchor.utils.bytes.utf8.encode(RESERVE_SEED)),
+          statePda.toBuffer(),
+        ],
+        program.programId
+      );
+    const before = await program.account.reserve.fetch(reservePda);
+
+    try {
+      await program.rpc.transferReserve(
+        { recipientNonce: reserveBump },
+        {
+          accounts: {
+            initializer: USER_KEYPAIR.publicKey,
+            reserve: reservePda,
+            recipient: USER_KEYPAIR.publicKey,
+            recipientReserve: reservePda,
+            state: statePda,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [USER_KEYPAIR],
+        }
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Cannot transfer a reserve to itself");
+    }
+
+    // a (locked) deposit cannot be pushed into a reserve of someone else
+    try {
+      await program.rpc.transferReserve(
+        { recipientNonce: recipientReserveBump },
+        {
+          accounts: {
+            initializer: USER_KEYPAIR.publicKey,
+            reserve: reservePda,
+            recipient: NEW_ADMIN_KEYPAIR.publicKey,
+            recipientReserve: recipientReservePda,
+            state: statePda,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [USER_KEYPAIR],
+        }
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.message, "Signature verification failed");
+    }
+    assert.ok(
+      (await provider.connection.getAccountInfo(recipientReservePda)) === null
+    );
+
+    // recipient reserve is created, with consent of the recipient
+    await program.rpc.transferReserve(
+      { recipientNonce: recipientReserveBump },
+      {
+        accounts: {
+          initializer: USER_KEYPAIR.publicKey,
+          reserve: reservePda,
+          recipient: NEW_ADMIN_KEYPAIR.publicKey,
+          recipientReserve: recipientReservePda,
+          state: statePda,
+          systemProgram: SystemProgram.programId,
+        },
+        signers: [USER_KEYPAIR, NEW_ADMIN_KEYPAIR],
+      }
+    );
+
+    let reserve = await program.account.reserve.fetch(reservePda);
+    assert.ok(reserve.depositAmount.eq(new BN("0")));
+    assert.ok(reserve.rewardAmount.eq(new BN("0")));
+    const recipientReserve = await program.account.reserve.fetch(
+      recipientReservePda
+    );
+    assert.ok(recipientReserve.nonce === recipientReserveBump);
+    assert.ok(recipientReserve.depositAmount.eq(before.depositAmount));
+    assert.ok(recipientReserve.rewardAmount.gte(before.rewardAmount));
+
+    // and back into the existing reserve
+    await program.rpc.transferReserve(
+      { recipientNonce: reserveBump },
+      {
+        accounts: {
+          initializer: NEW_ADMIN_KEYPAIR.publicKey,
+          reserve: recipientReservePda,
+          recipient: USER_KEYPAIR.publicKey,
+          recipientReserve: reservePda,
+          state: statePda,
+          systemProgram: SystemProgram.programId,
+        },
+        signers: [NEW_ADMIN_KEYPAIR, USER_KEYPAIR],
+      }
+    );
+
+    reserve = await program.account.reserve.fetch(reservePda);
+    assert.ok(reserve.depositAmount.eq(before.depositAmount));
+    assert.ok(reserve.rewardAmount.gte(before.rewardAmount));
+  });
+
+  it("should restrict guardian to pausing & locking", async () => {
+    await program.rpc.updateRoles(
+      {
+        guardian: USER_KEYPAIR.publicKey,
+        operator: PublicKey.default,
+        harvester: PublicKey.default,
+      },
+      {
+        accounts: {
+          admin: ADMIN_KEYPAIR.publicKey,
+          state: statePda,
+        },
+        signers: [ADMIN_KEYPAIR],
+      }
+    );
+
+    const updateState = (pauseFlags: number, authority: Keypair) =>
+      program.rpc.updateState(
+        { pauseFlags },
+        {
+          accounts: {
+            authority: authority.publicKey,
+            state: statePda,
+          },
+          signers: [authority],
+        }
+      );
+    const lockMarket = (lockFlag: boolean, authority: Keypair) =>
+      program.rpc.lockMarket(
+        { lockFlag },
+        {
+          accounts: {
+            authority: authority.publicKey,
+            market: marketPda,
+            state: statePda,
+          },
+          signers: [authority],
+        }
+      );
+
+    // guardian can pause & lock
+    await updateState(PAUSE_MINT, USER_KEYPAIR);
+    await lockMarket(true, USER_KEYPAIR);
+
+    // but can not resume
+    try {
+      await updateState(0, USER_KEYPAIR);
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Access denied");
+    }
+    try {
+      await lockMarket(false, USER_KEYPAIR);
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Access denied");
+    }
+
+    // admin resumes
+    await updateState(0, ADMIN_KEYPAIR);
+    await lockMarket(false, ADMIN_KEYPAIR);
+
+    // asserts
+    const state = await program.account.state.fetch(statePda);
+    assert.ok(state.guardian.equals(USER_KEYPAIR.publicKey));
+    assert.equal(state.pauseFlags, 0);
+    const market = await program.account.market.fetch(marketPda);
+    assert.ok(!market.lockFlag);
+  });
+
+  it("should transfer admin in two steps", async () => {
+    const proposeAdmin = (newAdmin: PublicKey) =>
+      program.rpc.proposeAdmin({
+        accounts: {
+          admin: ADMIN_KEYPAIR.publicKey,
+          newAdmin,
+          state: statePda,
+        },
+        signers: [ADMIN_KEYPAIR],
+      });
+
+    // propose & cancel
+    await proposeAdmin(USER_KEYPAIR.publicKey);
+    await program.rpc.cancelAdminTransfer({
+      accounts: {
+        admin: ADMIN_KEYPAIR.publicKey,
+        state: statePda,
+      },
+      signers: [ADMIN_KEYPAIR],
+    });
+    let state = await program.account.state.fetch(statePda);
+    assert.ok(state.admin.equals(ADMIN_KEYPAIR.publicKey));
+    assert.ok(state.pendingAdmin.equals(PublicKey.default));
+
+    // only the proposed admin can accept
+    await proposeAdmin(NEW_ADMIN_KEYPAIR.publicKey);
+    try {
+      await program.rpc.acceptAdmin({
+        accounts: {
+          pendingAdmin: USER_KEYPAIR.publicKey,
+          state: statePda,
+        },
+        signers: [USER_KEYPAIR],
+      });
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Access denied");
+    }
+
+    await program.rpc.acceptAdmin({
+      accounts: {
+        pendingAdmin: NEW_ADMIN_KEYPAIR.publicKey,
+        state: statePda,
+      },
+      signers: [NEW_ADMIN_KEYPAIR],
+    });
+
+    // asserts
+    state = await program.account.state.fetch(statePda);
+    assert.ok(state.admin.equals(NEW_ADMIN_KEYPAIR.publicKey));
+    assert.ok(state.pendingAdmin.equals(PublicKey.default));
+  });
+
+  it("should timelock admin actions", async () => {
+    const queuedActionPda = async (actionId: BN) =>
+      PublicKey.findProgramAddress(
+        [
+          statePda.toBuffer(),
+          Buffer.from(anchor.utils.bytes.utf8.encode(QUEUED_ACTION_SEED)),
+          actionId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+    const queueAction = async (action: any) => {
+      const state = await program.account.state.fetch(statePda);
+      const [actionPda, nonce] = await queuedActionPda(state.actionCount);
+      await program.rpc.queueAction(
+        { nonce, action },
+        {
+          accounts: {
+            admin: NEW_ADMIN_KEYPAIR.publicKey,
+            action: actionPda,
+            state: statePda,
+            systemProgram: SystemProgram.programId,
+          },
+          signers: [NEW_ADMIN_KEYPAIR],
+        }
+      );
+      return actionPda;
+    };
+    const executeQueuedAction = (actionPda: PublicKey) =>
+      program.rpc.executeQueuedAction({
+        accounts: {
+          admin: NEW_ADMIN_KEYPAIR.publicKey,
+          action: actionPda,
+          state: statePda,
+        },
+        remainingAccounts: [
+          {
+            pubkey: marketPda,
+            isWritable: true,
+            isSigner: false,
+          },
+        ],
+        signers: [NEW_ADMIN_KEYPAIR],
+      });
+
+    // enable timelock, applied instantly while timelock is disabled
+    await executeQueuedAction(
+      await queueAction({ setTimelockDelay: { delay: new BN(3600) } })
+    );
+    let state = await program.account.state.fetch(statePda);
+    assert.equal(state.timelockDelay.toNumber(), 3600);
+
+    // direct updates are rejected
+    try {
+      await program.rpc.updateMarket(
+        {
+          lockFlag: true,
+          withdrawalLiq: new BN("0"),
+          incrementWithdrawalLiq: true,
+        },
+        {
+          accounts: {
+            admin: NEW_ADMIN_KEYPAIR.publicKey,
+            market: marketPda,
+            state: statePda,
+          },
+          signers: [NEW_ADMIN_KEYPAIR],
+        }
+      );
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Timelock required, queue the action instead");
+    }
+
+    // queued updates wait for the delay
+    const actionPda = await queueAction({
+      updateMarket: {
+        market: marketPda,
+        args: {
+          lockFlag: true,
+          withdrawalLiq: new BN("0"),
+          incrementWithdrawalLiq: true,
+        },
+      },
+    });
+    try {
+      await executeQueuedAction(actionPda);
+      assert.fail();
+    } catch (err) {
+      assert.equal(err.msg, "Timelocked action is not ready");
+    }
+
+    await program.rpc.cancelQueuedAction({
+      accounts: {
+        admin: NEW_ADMIN_KEYPAIR.publicKey,
+        action: actionPda,
+        state: statePda,
+      },
+      signers: [NEW_ADMIN_KEYPAIR],
+    });
+    assert.equal(await provider.connection.getAccountInfo(actionPda), null);
+
+    // emergency pause stays instant
+    await program.rpc.updateState(
+      { pauseFlags: PAUSE_ALL },
+      {
+        accounts: {
+          authority: NEW_ADMIN_KEYPAIR.publicKey,
+          state: statePda,
+        },
+        signers: [NEW_ADMIN_KEYPAIR],
+      }
+    );
+    state = await program.account.state.fetch(statePda);
+    assert.equal(state.pauseFlags, PAUSE_ALL);
+  });
 });